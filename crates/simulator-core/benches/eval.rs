@@ -0,0 +1,59 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use simulator_core::cell::CellFlags;
+use simulator_core::{FPGA, FpgaIO};
+use std::hint::black_box;
+
+/// Builds a board where every other cell has some junctions,
+/// outputs and NOT functions, so that all the lines get simulated.
+fn board(size: usize) -> FPGA {
+    let mut fpga = FPGA::new(size, size);
+
+    for row in 0..size {
+        for col in (row % 2..size).step_by(2) {
+            let cell = fpga.get_mut(row, col).unwrap();
+            cell.flags |= CellFlags::JC1_R1 | CellFlags::JC2_R2 | CellFlags::NOT_C1;
+            cell.flags |= CellFlags::C1_OUT | CellFlags::C2_OUT | CellFlags::R1_OUT;
+        }
+    }
+
+    fpga
+}
+
+fn eval(c: &mut Criterion) {
+    let mut group = c.benchmark_group("eval");
+
+    for size in [8, 64, 256] {
+        let fpga = board(size);
+        let width = size * 2;
+
+        let patterns: [(&str, Box<[bool]>); 3] = [
+            ("zeros", vec![false; width].into_boxed_slice()),
+            ("ones", vec![true; width].into_boxed_slice()),
+            ("alternating", (0..width).map(|i| i % 2 == 0).collect()),
+        ];
+
+        // Reported as cell evaluations per second.
+        group.throughput(Throughput::Elements((size * size) as u64));
+
+        let compiled = fpga.compile();
+
+        for (name, bits) in patterns {
+            let input = FpgaIO::from(bits);
+            group.bench_with_input(
+                BenchmarkId::new(name, format!("{size}x{size}")),
+                &input,
+                |b, input| b.iter(|| fpga.eval(black_box(input.clone())).unwrap()),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("compiled/{name}"), format!("{size}x{size}")),
+                &input,
+                |b, input| b.iter(|| compiled.eval(black_box(input.clone())).unwrap()),
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, eval);
+criterion_main!(benches);
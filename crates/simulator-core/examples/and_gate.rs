@@ -0,0 +1,47 @@
+//! Builds a board made of a single AND gate and prints
+//! its truth table.
+//!
+//! ```text
+//! cargo run --example and_gate
+//! ```
+
+use simulator_core::cell::{ActivationOrder, Cell, CellFlags, Fills, Selector};
+use simulator_core::{FPGA, FpgaIoBuilder};
+
+fn main() -> Result<(), &'static str> {
+    // Both columns are joined to Row 1, so that Column 1 is only
+    // left on when both inputs are. It's the AND cell of the
+    // palette of the simulator.
+    let order = ActivationOrder::new([
+        Selector::Column2,
+        Selector::Row1,
+        Selector::Column1,
+        Selector::Row2,
+    ])?;
+    let and = Cell::new(
+        &order,
+        &(CellFlags::JC1_R1 | CellFlags::JC2_R1),
+        Fills::new(2, 2, 2, 0),
+    );
+    let fpga = FPGA::from_cells(1, 1, vec![and])?;
+
+    println!(" A | B | A AND B");
+    println!("---+---+--------");
+    for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+        let input = FpgaIoBuilder::new(1)
+            .set(0, Selector::Column1, a)?
+            .set(0, Selector::Column2, b)?
+            .build();
+        let output = fpga
+            .eval(input)
+            .map_err(|_| "Failed to evaluate the board")?;
+        let result = output
+            .line(0, Selector::Column1)
+            .ok_or("The output has no column")?;
+
+        println!(" {} | {} | {}", a as u8, b as u8, result as u8);
+        assert_eq!(result, a && b);
+    }
+
+    Ok(())
+}
@@ -8,10 +8,14 @@
 //! the simulation can run as fast as possible.
 
 use crate::impl_set_range;
+use alloc::format;
+use alloc::vec::Vec;
 use bitflags::{Flags, bitflags};
+use core::fmt;
+use core::ops::{Index, IndexMut};
+use core::str::FromStr;
 use serde::de::EnumAccess;
-use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[repr(u8)]
 #[derive(Hash, Eq, PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
@@ -22,13 +26,83 @@ pub enum Selector {
     Row2 = 3,
 }
 
+impl fmt::Display for Selector {
+    /// Writes the short name of the line, e.g. `C1`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Selector::Column1 => "C1",
+            Selector::Column2 => "C2",
+            Selector::Row1 => "R1",
+            Selector::Row2 => "R2",
+        })
+    }
+}
+
+impl FromStr for Selector {
+    type Err = &'static str;
+
+    /// Parses the short name of a line (`C1`, `C2`, `R1`
+    /// or `R2`), ignoring its case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "C1" => Ok(Selector::Column1),
+            "C2" => Ok(Selector::Column2),
+            "R1" => Ok(Selector::Row1),
+            "R2" => Ok(Selector::Row2),
+            _ => Err("Unknown line, expected one of C1, C2, R1 or R2"),
+        }
+    }
+}
+
+impl TryFrom<u8> for Selector {
+    type Error = &'static str;
+
+    /// Converts the index of a line, its discriminant.
+    fn try_from(index: u8) -> Result<Self, Self::Error> {
+        match index {
+            0 => Ok(Selector::Column1),
+            1 => Ok(Selector::Column2),
+            2 => Ok(Selector::Row1),
+            3 => Ok(Selector::Row2),
+            _ => Err("Line index out of range, expected 0 to 3"),
+        }
+    }
+}
+
 /// This struct is used to describe in which order the cell
 /// columns and rows activate. This order is crucial in
 /// defining what will be the logic function characteristic
 /// of the [Cell].
-#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy, Serialize)]
 pub struct ActivationOrder([Selector; 4]);
 
+impl<'de> Deserialize<'de> for ActivationOrder {
+    /// Goes through [ActivationOrder::new] so that orders
+    /// containing duplicates are rejected.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let order = <[Selector; 4]>::deserialize(deserializer)?;
+        ActivationOrder::new(order).map_err(serde::de::Error::custom)
+    }
+}
+
+impl TryFrom<[u8; 4]> for ActivationOrder {
+    type Error = &'static str;
+
+    /// Reads an order given as the indices of its lines, see
+    /// [Selector::try_from] and [ActivationOrder::new].
+    fn try_from(order: [u8; 4]) -> Result<Self, Self::Error> {
+        let [a, b, c, d] = order;
+        ActivationOrder::new([a.try_into()?, b.try_into()?, c.try_into()?, d.try_into()?])
+    }
+}
+
+impl From<ActivationOrder> for [u8; 4] {
+    /// The indices of the lines, in activation order.
+    fn from(order: ActivationOrder) -> Self {
+        order.0.map(|sel| sel as u8)
+    }
+}
+
 impl IntoIterator for ActivationOrder {
     type Item = Selector;
     type IntoIter = core::array::IntoIter<Selector, 4>;
@@ -75,35 +149,108 @@ impl ActivationOrder {
     /// assert!(ActivationOrder::new([Selector::Column1, Selector::Column1, Selector::Row1, Selector::Row2]).is_err());
     /// ```
     pub fn new(order: [Selector; 4]) -> Result<Self, &'static str> {
-        let set: HashSet<_> = order.iter().collect();
-        if set.len() != 4 {
+        // Each line sets its own bit, so only 4 distinct
+        // lines can set all of them.
+        let seen = order.iter().fold(0u8, |seen, sel| seen | 1 << *sel as u8);
+        if seen != 0b1111 {
             return Err("Duplicate enum variants not allowed");
         }
         Ok(ActivationOrder(order))
     }
+
+    /// Returns when the line described by `sel` activates,
+    /// as its 0-based index in the order.
+    #[inline]
+    pub fn position_of(&self, sel: Selector) -> usize {
+        self.0
+            .iter()
+            .position(|line| *line == sel)
+            .expect("Every line appears in the order")
+    }
+}
+
+/// A value for each line of a [Cell], indexed by its [Selector]
+/// so that lines can't be mixed up with some other raw index.
+/// It's serialized just like the `[T; 4]` it wraps, in the
+/// order of the [Selector] variants.
+#[derive(Hash, Eq, PartialEq, Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LineArray<T>([T; 4]);
+
+impl<T> LineArray<T> {
+    /// Creates a [LineArray] from the values of Col 1,
+    /// Col 2, Row 1 and Row 2, in this order.
+    #[inline]
+    pub const fn new(lines: [T; 4]) -> Self {
+        Self(lines)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, T> {
+        self.0.iter()
+    }
+}
+
+impl<T> Index<Selector> for LineArray<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, sel: Selector) -> &T {
+        &self.0[sel as usize]
+    }
+}
+
+impl<T> IndexMut<Selector> for LineArray<T> {
+    #[inline]
+    fn index_mut(&mut self, sel: Selector) -> &mut T {
+        &mut self.0[sel as usize]
+    }
+}
+
+impl<T> IntoIterator for LineArray<T> {
+    type Item = T;
+    type IntoIter = core::array::IntoIter<T, 4>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
 }
 
 /// This struct represents the amount of filler
 /// blocks on each [Cell] line.
-#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
-pub struct Fills([u8; 4]);
+#[derive(Hash, Eq, PartialEq, Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Fills(LineArray<u8>);
+
+impl From<LineArray<u8>> for Fills {
+    #[inline]
+    fn from(lines: LineArray<u8>) -> Self {
+        Self(lines)
+    }
+}
 
 impl Fills {
+    /// The maximum amount of filler blocks a line can have, so
+    /// that with the default [SimParams] the block count of a line,
+    /// with every block the simulation adds, fits in a `u8` as in
+    /// the Ghost Block design. The count itself is a `u16`, so
+    /// it's a limit of the file format, not of custom [SimParams].
+    pub const MAX: u8 = u8::MAX - Cell::FIXED_BLOCKS - 6;
+
     #[inline]
-    fn new(c1: u8, c2: u8, r1: u8, r2: u8) -> Self {
-        Self([c1, c2, r1, r2])
+    pub fn new(c1: u8, c2: u8, r1: u8, r2: u8) -> Self {
+        Self(LineArray::new([c1, c2, r1, r2]))
     }
 
-    /// Sets the amount of filler blocks of the given line.
+    /// Gets the amount of filler blocks of the line described by `sel`.
     #[inline]
-    fn set(&mut self, target: u8, val: u8) {
-        self.0[target as usize] = val;
+    pub fn get_line(&self, sel: Selector) -> u8 {
+        self.0[sel]
     }
 
-    /// Gets the amount of filler blocks of the given line.
+    /// Sets the amount of filler blocks of the line described by `sel`.
     #[inline]
-    fn get(&self, target: u8) -> u8 {
-        self.0[target as usize]
+    pub fn set_line(&mut self, sel: Selector, val: u8) {
+        self.0[sel] = val;
     }
 }
 
@@ -121,6 +268,11 @@ bitflags! {
 }
 
 impl CellIO {
+    /// Both the columns.
+    pub const COLUMNS: CellIO = CellIO::COLUMN_1.union(CellIO::COLUMN_2);
+    /// Both the rows.
+    pub const ROWS: CellIO = CellIO::ROW_1.union(CellIO::ROW_2);
+
     #[inline]
     pub fn new(c1: bool, c2: bool, r1: bool, r2: bool) -> Self {
         let mut var = CellIO::empty();
@@ -135,6 +287,77 @@ impl CellIO {
     pub fn contains_as_u8(&self, flag: CellIO) -> u8 {
         (*self & flag).bits() >> flag.bits().trailing_zeros()
     }
+
+    /// If any of the columns carries a signal.
+    #[inline]
+    pub fn any_column(&self) -> bool {
+        self.intersects(CellIO::COLUMNS)
+    }
+
+    /// If any of the rows carries a signal.
+    #[inline]
+    pub fn any_row(&self) -> bool {
+        self.intersects(CellIO::ROWS)
+    }
+
+    /// Iterates over the 16 possible combinations of lines,
+    /// ordered by their bits.
+    ///
+    /// It isn't called `all` since [CellIO::all] already
+    /// returns the [CellIO] with every line set.
+    #[inline]
+    pub fn combinations() -> impl DoubleEndedIterator<Item = CellIO> + ExactSizeIterator {
+        (0..16).map(CellIO::from_bits_truncate)
+    }
+
+    /// Returns the flag of the line described by `sel`.
+    #[inline]
+    fn line(sel: Selector) -> Self {
+        match sel {
+            Selector::Column1 => CellIO::COLUMN_1,
+            Selector::Column2 => CellIO::COLUMN_2,
+            Selector::Row1 => CellIO::ROW_1,
+            Selector::Row2 => CellIO::ROW_2,
+        }
+    }
+
+    /// Creates a [CellIO] where only the line described by
+    /// `sel` is set to `val`.
+    #[inline]
+    pub fn from_selector_bool(sel: Selector, val: bool) -> Self {
+        let mut var = CellIO::empty();
+        var.set_line(sel, val);
+        var
+    }
+
+    /// Sets the line described by `sel` to `val`.
+    #[inline]
+    pub fn set_line(&mut self, sel: Selector, val: bool) {
+        self.set(Self::line(sel), val);
+    }
+
+    /// Gets the value of the line described by `sel`.
+    #[inline]
+    pub fn get_line(&self, sel: Selector) -> bool {
+        self.contains(Self::line(sel))
+    }
+}
+
+// [CellIO] is always (de)serialized as its raw bits, even in
+// human-readable formats, so that IO vectors stay compact.
+impl Serialize for CellIO {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.bits())
+    }
+}
+
+impl<'de> Deserialize<'de> for CellIO {
+    /// Rejects any bit outside of the 4 defined lines.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bits = u8::deserialize(deserializer)?;
+        CellIO::from_bits(bits)
+            .ok_or_else(|| serde::de::Error::custom(format!("Invalid CellIO bits {bits:#06b}")))
+    }
 }
 
 bitflags! {
@@ -170,7 +393,7 @@ bitflags! {
     /// [u8] bitflag would've just increased the cache misses without
     /// any other benefit, since we would be using 24 bits instead of
     /// 16.
-    #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+    #[derive(Hash, Eq, PartialEq, Debug, Serialize, Clone, Copy)]
     pub struct CellFlags: u16 {
         // Junction between Col 1 and Row 1.
         const JC1_R1 = 1 << 0;
@@ -206,6 +429,10 @@ bitflags! {
         const STILL_C2 = 1 << 11;
         // If Row 1 stood still(or didn't move yet, same thing).
         const STILL_R1 = 1 << 12;
+
+        // The cell is a ghost: it isn't part of the design and
+        // every signal passes through it untouched.
+        const DISABLED = 1 << 13;
     }
 }
 
@@ -227,7 +454,122 @@ impl Default for CellFlags {
     }
 }
 
+impl<'de> Deserialize<'de> for CellFlags {
+    /// Goes through [CellFlags::from_bits_checked] so that the
+    /// STILL_XY flags are always set after loading a [Cell].
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let flags: CellFlags = bitflags::serde::deserialize(deserializer)?;
+        Ok(CellFlags::from_bits_checked(flags.bits()))
+    }
+}
+
 impl CellFlags {
+    /// The simulation-only flags, which must never be edited.
+    pub const SIMULATION: CellFlags = CellFlags::STILL_C1
+        .union(CellFlags::STILL_C2)
+        .union(CellFlags::STILL_R1);
+
+    /// The flags describing the design of a [Cell], which
+    /// are all of them except the [CellFlags::SIMULATION] ones.
+    pub const EDITABLE: CellFlags = CellFlags::all().difference(CellFlags::SIMULATION);
+
+    /// The junctions between each column and row.
+    pub const JUNCTIONS: CellFlags = CellFlags::JC1_R1
+        .union(CellFlags::JC1_R2)
+        .union(CellFlags::JC2_R1)
+        .union(CellFlags::JC2_R2);
+
+    /// The outputs of each column and row.
+    pub const OUTPUTS: CellFlags = CellFlags::C1_OUT
+        .union(CellFlags::C2_OUT)
+        .union(CellFlags::R1_OUT)
+        .union(CellFlags::R2_OUT);
+
+    /// The NOT functions, only available on the columns.
+    pub const NOTS: CellFlags = CellFlags::NOT_C1.union(CellFlags::NOT_C2);
+
+    /// Returns only the [CellFlags::JUNCTIONS] that are set.
+    #[inline]
+    pub fn junctions(&self) -> CellFlags {
+        self.intersection(CellFlags::JUNCTIONS)
+    }
+
+    /// Returns only the [CellFlags::OUTPUTS] that are set.
+    #[inline]
+    pub fn outputs(&self) -> CellFlags {
+        self.intersection(CellFlags::OUTPUTS)
+    }
+
+    /// Returns only the [CellFlags::NOTS] that are set.
+    #[inline]
+    pub fn nots(&self) -> CellFlags {
+        self.intersection(CellFlags::NOTS)
+    }
+
+    /// Checks that the flags describe a cell that can be built,
+    /// returning a description of each rule that's broken:
+    ///
+    /// - The [CellFlags::SIMULATION] flags must be set, they're
+    ///   only ever cleared while the cell is being evaluated.
+    /// - An output must be on a line connected to another one,
+    ///   otherwise it only repeats the input of its own line.
+    ///   A column is connected by its junctions and its NOT,
+    ///   R1 by its junctions and the NOTs, which feed blocks
+    ///   into it, and R2 only by its junctions.
+    pub fn check_consistency(&self) -> Result<(), Vec<&'static str>> {
+        let mut problems = Vec::new();
+
+        if !self.contains(CellFlags::SIMULATION) {
+            problems.push("STILL_C1, STILL_C2 and STILL_R1 must be set");
+        }
+
+        let outputs = [
+            (
+                CellFlags::C1_OUT,
+                CellFlags::JC1_R1 | CellFlags::JC1_R2 | CellFlags::NOT_C1,
+                "C1_OUT needs a junction or NOT on Col 1",
+            ),
+            (
+                CellFlags::C2_OUT,
+                CellFlags::JC2_R1 | CellFlags::JC2_R2 | CellFlags::NOT_C2,
+                "C2_OUT needs a junction or NOT on Col 2",
+            ),
+            (
+                CellFlags::R1_OUT,
+                CellFlags::JC1_R1 | CellFlags::JC2_R1 | CellFlags::NOTS,
+                "R1_OUT needs a junction or NOT on Row 1",
+            ),
+            (
+                CellFlags::R2_OUT,
+                CellFlags::JC1_R2 | CellFlags::JC2_R2,
+                "R2_OUT needs a junction on Row 2",
+            ),
+        ];
+        for (output, connections, problem) in outputs {
+            if self.contains(output) && !self.intersects(connections) {
+                problems.push(problem);
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Parses the name of one of the [CellFlags::EDITABLE]
+    /// flags, e.g. `NOT_C1`, ignoring its case.
+    pub fn from_editable_name(name: &str) -> Result<Self, &'static str> {
+        let flag = CellFlags::from_name(&name.to_ascii_uppercase()).ok_or("Unknown flag")?;
+
+        if !CellFlags::EDITABLE.contains(flag) {
+            return Err("STILL_* flags are simulation-only and can't be edited");
+        }
+
+        Ok(flag)
+    }
+
     /// This converts the given `bits` to a [CellFlags]
     /// and sets the various STILL_XY flags to 1 as required.
     ///
@@ -252,21 +594,21 @@ impl CellFlags {
 ///
 /// ## Arguments
 ///
-/// - `target`: what [Fills] index represents that given line.
+/// - `target`: the [Selector] of that given line.
 /// - `flags`: this is an array of [CellFlags] const(not of
-///    instances), it represents the set of [CellFlags] flags
-///    relevant to that line.
+///   instances), it represents the set of [CellFlags] flags
+///   relevant to that line.
 ///
 #[derive(Debug, Clone, Copy)]
 struct TargetGroup<const N: usize> {
-    target: u8,
+    target: Selector,
     cell_io: CellIO,
     flags: [CellFlags; N],
 }
 
 impl TargetGroup<5> {
     const C1: TargetGroup<5> = TargetGroup {
-        target: 0,
+        target: Selector::Column1,
         cell_io: CellIO::COLUMN_1,
         flags: [
             CellFlags::JC1_R1,
@@ -278,7 +620,7 @@ impl TargetGroup<5> {
     };
 
     const C2: TargetGroup<5> = TargetGroup {
-        target: 1,
+        target: Selector::Column2,
         cell_io: CellIO::COLUMN_2,
         flags: [
             CellFlags::JC2_R1,
@@ -303,29 +645,59 @@ impl From<TargetGroup<5>> for TargetGroup<3> {
 
 impl TargetGroup<3> {
     const R1: TargetGroup<3> = TargetGroup {
-        target: 2,
+        target: Selector::Row1,
         cell_io: CellIO::ROW_1,
         flags: [CellFlags::JC1_R1, CellFlags::JC2_R1, CellFlags::R1_OUT],
     };
     const R2: TargetGroup<3> = TargetGroup {
-        target: 3,
+        target: Selector::Row2,
         cell_io: CellIO::ROW_2,
         flags: [CellFlags::JC1_R2, CellFlags::JC2_R2, CellFlags::R2_OUT],
     };
 }
 
-#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+/// A single FPGA cell.
+///
+/// Two cells compare equal (and hash the same) when their
+/// activation order, flags and fills are identical. The
+/// simulation-only STILL_* flags are always initialised the same
+/// way, so they can't make equal configurations compare unequal.
+/// Cells configured differently can still compute the same
+/// function, compare their [Cell::truth_table]s for that.
+#[derive(Hash, Eq, PartialEq, Debug, Default, Clone, Copy, Serialize, Deserialize)]
 pub struct Cell {
     pub activation_order: ActivationOrder,
     pub flags: CellFlags,
     pub fills: Fills,
 }
 
-type LineEvalFn = fn(&mut Cell, &mut CellIO);
+/// The block-count rules a [Cell] is simulated with, see
+/// [Cell::eval_cell_with]. The [Default] ones are the rules
+/// of the Ghost Block design.
+#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
+pub struct SimParams {
+    /// The fixed amount of blocks that each line is made of.
+    pub fixed_blocks: u8,
+    /// A line activates when its block count is above it.
+    pub threshold: u8,
+}
+
+impl Default for SimParams {
+    fn default() -> Self {
+        Self {
+            fixed_blocks: Cell::FIXED_BLOCKS,
+            threshold: Cell::THRESHOLD,
+        }
+    }
+}
+
+type LineEvalFn = fn(&mut Cell, &mut CellIO, &SimParams);
 
 impl Cell {
     /// The fixed amount of blocks that each line is made of.
-    const FIXED_BLOCKS: u8 = 9;
+    pub const FIXED_BLOCKS: u8 = 9;
+    /// A line activates when its block count is above it.
+    pub const THRESHOLD: u8 = 12;
     const EVAL_TABLE: [LineEvalFn; 4] = [
         Self::sim_col1,
         Self::sim_col2,
@@ -335,24 +707,129 @@ impl Cell {
 
     #[inline]
     pub fn new(activation_order: &ActivationOrder, flags: &CellFlags, fills: Fills) -> Self {
-        let mut flags = flags.clone();
+        let mut flags = *flags;
         flags.set_range(10, 3);
         Self {
-            activation_order: activation_order.clone().clone(),
+            activation_order: *activation_order,
             flags,
             fills,
         }
     }
 
+    /// Sets the [CellFlags::SIMULATION] flags again, which the
+    /// simulation requires, fixing the cell after its flags
+    /// have been edited without [Cell::new].
+    #[inline]
+    pub fn reset_sim_flags(&mut self) {
+        self.flags.set_range(10, 3);
+    }
+
+    /// Moves everything configured on each line of the cell to the
+    /// line given by `map`, which must be a permutation of them: the
+    /// activation order, the junctions, the outputs, the NOTs and the
+    /// fills. The STILL_XY flags are set again and the cell stays
+    /// disabled if it was.
+    ///
+    /// Rows have no NOT and both NOTs always feed Row 1, so a cell
+    /// with a NOT can only have its columns swapped. Within those
+    /// limits the remapped cell computes the same function, on
+    /// the remapped lines.
+    ///
+    /// ## Returns
+    ///
+    /// - [Err()] if `map` isn't a permutation, moves a NOT on a row,
+    ///   moves Row 1 of a cell with a NOT or leaves a junction
+    ///   without a column or a row.
+    pub fn remap_lines(&self, map: impl Fn(Selector) -> Selector) -> Result<Self, &'static str> {
+        const OUTPUTS: [(CellFlags, Selector); 4] = [
+            (CellFlags::C1_OUT, Selector::Column1),
+            (CellFlags::C2_OUT, Selector::Column2),
+            (CellFlags::R1_OUT, Selector::Row1),
+            (CellFlags::R2_OUT, Selector::Row2),
+        ];
+        const JUNCTIONS: [(CellFlags, Selector, Selector); 4] = [
+            (CellFlags::JC1_R1, Selector::Column1, Selector::Row1),
+            (CellFlags::JC1_R2, Selector::Column1, Selector::Row2),
+            (CellFlags::JC2_R1, Selector::Column2, Selector::Row1),
+            (CellFlags::JC2_R2, Selector::Column2, Selector::Row2),
+        ];
+
+        let activation_order = ActivationOrder::new(self.activation_order.0.map(&map))?;
+        let mut flags = self.flags.intersection(CellFlags::DISABLED);
+
+        for (output, line) in OUTPUTS {
+            if self.flags.contains(output) {
+                let (output, _) = OUTPUTS[map(line) as usize];
+                flags |= output;
+            }
+        }
+        for (not, line) in [
+            (CellFlags::NOT_C1, Selector::Column1),
+            (CellFlags::NOT_C2, Selector::Column2),
+        ] {
+            if self.flags.contains(not) {
+                flags |= match map(line) {
+                    Selector::Column1 => CellFlags::NOT_C1,
+                    Selector::Column2 => CellFlags::NOT_C2,
+                    _ => return Err("Only columns can be negated"),
+                };
+            }
+        }
+        if self.flags.intersects(CellFlags::NOTS) && map(Selector::Row1) != Selector::Row1 {
+            return Err("The NOTs always feed Row 1");
+        }
+        for (junction, column, row) in JUNCTIONS {
+            if self.flags.contains(junction) {
+                let (a, b) = (map(column), map(row));
+                let (found, _, _) = JUNCTIONS
+                    .into_iter()
+                    .find(|&(_, column, row)| (column, row) == (a, b) || (row, column) == (a, b))
+                    .ok_or("A junction must connect a column and a row")?;
+                flags |= found;
+            }
+        }
+
+        let mut fills = LineArray::default();
+        for (_, line) in OUTPUTS {
+            fills[map(line)] = self.fills.get_line(line);
+        }
+
+        Ok(Cell::new(&activation_order, &flags, Fills::from(fills)))
+    }
+
+    /// Whether the cell is configured like [Cell::default],
+    /// meaning it isn't used by the design.
+    #[inline]
+    pub fn is_default(&self) -> bool {
+        *self == Cell::default()
+    }
+
+    /// Checks the invariants of the [Cell], returning a
+    /// description of each one that isn't respected.
+    pub(crate) fn check(&self) -> Vec<&'static str> {
+        let mut problems = Vec::new();
+
+        if !self.flags.contains(CellFlags::SIMULATION) {
+            problems.push("STILL_C1, STILL_C2 and STILL_R1 must be set");
+        }
+
+        if self.fills.0.iter().any(|fill| *fill > Fills::MAX) {
+            problems.push("Fills exceed the maximum amount of filler blocks");
+        }
+
+        problems
+    }
+
     /// Calculates the amount of blocks on the given `group`.
     #[inline]
-    fn count(&self, input: CellIO, group: TargetGroup<3>) -> u8 {
-        Self::FIXED_BLOCKS
-            + self.fills.get(group.target)
-            + input.contains_as_u8(group.cell_io)
-            + (self.flags.contains(group.flags[0]) as u8)
-            + (self.flags.contains(group.flags[1]) as u8)
-            + (self.flags.contains(group.flags[2]) as u8)
+    fn count(&self, input: CellIO, group: TargetGroup<3>, params: &SimParams) -> u16 {
+        // Counted as u16 so that no parameters can overflow it.
+        params.fixed_blocks as u16
+            + self.fills.get_line(group.target) as u16
+            + input.contains_as_u8(group.cell_io) as u16
+            + (self.flags.contains(group.flags[0]) as u16)
+            + (self.flags.contains(group.flags[1]) as u16)
+            + (self.flags.contains(group.flags[2]) as u16)
     }
 
     /// Simulates the specified column with the specified inputs.
@@ -361,12 +838,12 @@ impl Cell {
     ///
     /// - `column_input`:
     #[inline]
-    fn sim_column(&mut self, mut input: &mut CellIO, group: TargetGroup<5>) {
-        let mut count: u8 = self.count(*input, TargetGroup::from(group));
+    fn sim_column(&mut self, mut input: &mut CellIO, group: TargetGroup<5>, params: &SimParams) {
+        let mut count = self.count(*input, TargetGroup::from(group), params);
 
         let out = (self.flags.contains(group.flags[3])
             && !self.flags.contains(CellFlags::STILL_R1))
-            || count > 12;
+            || count > params.threshold as u16;
 
         if !out {
             self.flags.set(group.flags[0], false);
@@ -378,22 +855,22 @@ impl Cell {
     }
 
     #[inline(always)]
-    fn sim_col1(&mut self, input: &mut CellIO) {
-        self.sim_column(input, TargetGroup::C1);
+    fn sim_col1(&mut self, input: &mut CellIO, params: &SimParams) {
+        self.sim_column(input, TargetGroup::C1, params);
     }
 
     #[inline(always)]
-    fn sim_col2(&mut self, input: &mut CellIO) {
-        self.sim_column(input, TargetGroup::C2);
+    fn sim_col2(&mut self, input: &mut CellIO, params: &SimParams) {
+        self.sim_column(input, TargetGroup::C2, params);
     }
 
     #[inline]
-    fn sim_row1(&mut self, mut input: &mut CellIO) {
-        let mut count: u8 = self.count(*input, TargetGroup::R1)
-            + (self.flags.contains(CellFlags::NOT_C1) as u8)
-            + (self.flags.contains(CellFlags::NOT_C2) as u8);
+    fn sim_row1(&mut self, mut input: &mut CellIO, params: &SimParams) {
+        let mut count = self.count(*input, TargetGroup::R1, params)
+            + (self.flags.contains(CellFlags::NOT_C1) as u16)
+            + (self.flags.contains(CellFlags::NOT_C2) as u16);
 
-        let out = count > 12
+        let out = count > params.threshold as u16
             || (self.flags.contains(CellFlags::NOT_C1)
                 && !self.flags.contains(CellFlags::STILL_C1))
             || (self.flags.contains(CellFlags::NOT_C2)
@@ -408,10 +885,10 @@ impl Cell {
     }
 
     #[inline]
-    fn sim_row2(&mut self, mut input: &mut CellIO) {
-        let mut count: u8 = self.count(*input, TargetGroup::R2);
+    fn sim_row2(&mut self, mut input: &mut CellIO, params: &SimParams) {
+        let mut count = self.count(*input, TargetGroup::R2, params);
 
-        let out = count > 12;
+        let out = count > params.threshold as u16;
         if !out {
             self.flags.set(CellFlags::JC1_R2, false);
             self.flags.set(CellFlags::JC2_R2, false);
@@ -419,17 +896,81 @@ impl Cell {
         input.set(CellIO::ROW_2, out);
     }
 
+    /// Evaluates the cell with the given `input`.
+    ///
+    /// A [`DISABLED`](CellFlags::DISABLED) cell returns `input`
+    /// untouched: its columns carry their input to the next row
+    /// and its rows carry theirs to the next cell of the sweep.
+    ///
+    /// ## Purity
+    ///
+    /// While a line is simulated the flags of its junctions and
+    /// its STILL_XY flag are cleared when it doesn't fire, so that
+    /// the lines activating after it see its blocks falling. That
+    /// state only lives in a copy of the cell made for each call:
+    /// `self` is never changed and the same `input` always gives
+    /// the same output.
+    #[inline]
+    pub fn eval_cell(&self, input: CellIO) -> CellIO {
+        self.eval_cell_with(input, &SimParams::default())
+    }
+
+    /// Evaluates the cell like [Cell::eval_cell] does, but
+    /// following the block-count rules given by `params`.
+    /// It's just as pure.
+    #[inline]
+    pub fn eval_cell_with(&self, input: CellIO, params: &SimParams) -> CellIO {
+        self.eval_cell_verbose_with(input, params).0
+    }
+
+    /// Evaluates the cell like [Cell::eval_cell] does, also returning
+    /// which of its gates fired: the junctions that weren't cleared by
+    /// a line that didn't fire and the outputs whose line fired.
+    /// A [`DISABLED`](CellFlags::DISABLED) cell has none.
+    #[inline]
+    pub fn eval_cell_verbose(&self, input: CellIO) -> (CellIO, CellFlags) {
+        self.eval_cell_verbose_with(input, &SimParams::default())
+    }
+
+    /// Evaluates the cell like [Cell::eval_cell_verbose] does, but
+    /// following the block-count rules given by `params`.
     #[inline]
-    pub fn eval_cell(&self, mut input: CellIO) -> CellIO {
-        let mut rtm_cell = self.clone();
+    pub fn eval_cell_verbose_with(
+        &self,
+        mut input: CellIO,
+        params: &SimParams,
+    ) -> (CellIO, CellFlags) {
+        if self.flags.contains(CellFlags::DISABLED) {
+            return (input, CellFlags::empty());
+        }
+
+        let mut rtm_cell = *self;
 
         for selector in rtm_cell.activation_order.0.clone().iter() {
-            Self::EVAL_TABLE[*selector as usize](&mut rtm_cell, &mut input);
+            Self::EVAL_TABLE[*selector as usize](&mut rtm_cell, &mut input, params);
         }
 
-        input
+        let mut fired = rtm_cell.flags.junctions();
+        for (output, line) in [
+            (CellFlags::C1_OUT, CellIO::COLUMN_1),
+            (CellFlags::C2_OUT, CellIO::COLUMN_2),
+            (CellFlags::R1_OUT, CellIO::ROW_1),
+            (CellFlags::R2_OUT, CellIO::ROW_2),
+        ] {
+            fired.set(output, self.flags.contains(output) && input.contains(line));
+        }
+
+        (input, fired)
+    }
+
+    /// Evaluates the cell for every possible input, the
+    /// output for a given input is at the index of its bits.
+    #[inline]
+    pub fn truth_table(&self) -> [CellIO; 16] {
+        core::array::from_fn(|i| self.eval_cell(CellIO::from_bits_truncate(i as u8)))
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     pub fn print_truth_table(&self) {
         let header = [
@@ -443,8 +984,7 @@ impl Cell {
         );
         println!("+-----+-----+-----+-----+---------+---------+---------+---------+");
 
-        for i in (0..16).rev() {
-            let input = CellIO::from_bits_truncate(i as u8);
+        for input in CellIO::combinations().rev() {
             let eval = self.eval_cell(input);
 
             println!(
@@ -467,6 +1007,10 @@ impl Cell {
 #[cfg(test)]
 mod cell_tests {
     use crate::cell::{ActivationOrder, Cell, CellFlags, Fills};
+    use alloc::collections::BTreeSet;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
 
     impl CellFlags {
         const FIXED_BLOCKS: u8 = 9;
@@ -508,6 +1052,94 @@ mod cell_tests {
         assert_eq!(cell.contains_as_u8(CellIO::ROW_2), 1);
     }
 
+    #[test]
+    fn cell_io_groups() {
+        assert_eq!(CellIO::COLUMNS | CellIO::ROWS, CellIO::all());
+        assert!(!CellIO::COLUMNS.intersects(CellIO::ROWS));
+
+        let io = CellIO::new(false, true, false, false);
+        assert!(io.any_column());
+        assert!(!io.any_row());
+
+        let io = CellIO::new(false, false, true, false);
+        assert!(!io.any_column());
+        assert!(io.any_row());
+
+        assert!(!CellIO::empty().any_column());
+        assert!(!CellIO::empty().any_row());
+    }
+
+    #[test]
+    fn cell_io_combinations() {
+        let combinations: Vec<_> = CellIO::combinations().collect();
+        assert_eq!(combinations.len(), 16);
+        assert_eq!(CellIO::combinations().len(), 16);
+
+        let distinct: BTreeSet<_> = combinations.iter().map(|io| io.bits()).collect();
+        assert_eq!(distinct.len(), 16);
+        assert_eq!(combinations.first(), Some(&CellIO::empty()));
+        assert_eq!(combinations.last(), Some(&CellIO::all()));
+    }
+
+    #[test]
+    fn cell_io_lines() {
+        let selectors = [
+            Selector::Column1,
+            Selector::Column2,
+            Selector::Row1,
+            Selector::Row2,
+        ];
+
+        for sel in selectors {
+            let mut cell = CellIO::empty();
+            cell.set_line(sel, true);
+            assert_eq!(cell, CellIO::from_selector_bool(sel, true));
+
+            for other in selectors {
+                assert_eq!(cell.get_line(other), other == sel);
+            }
+
+            cell.set_line(sel, false);
+            assert_eq!(cell, CellIO::empty());
+        }
+
+        assert_eq!(
+            CellIO::from_selector_bool(Selector::Row2, true),
+            CellIO::ROW_2
+        );
+    }
+
+    #[test]
+    fn cell_io_serialization() {
+        let io = CellIO::new(true, false, true, true);
+        let json = serde_json::to_string(&io).unwrap();
+        assert_eq!(json, "13");
+        assert_eq!(serde_json::from_str::<CellIO>(&json).unwrap(), io);
+
+        let ios: Vec<CellIO> = serde_json::from_str("[0, 1, 15]").unwrap();
+        assert_eq!(ios, vec![CellIO::empty(), CellIO::COLUMN_1, CellIO::all()]);
+
+        assert!(serde_json::from_str::<CellIO>("16").is_err());
+        assert!(serde_json::from_str::<CellIO>("255").is_err());
+    }
+
+    #[test]
+    fn selector_parsing() {
+        for sel in [
+            Selector::Column1,
+            Selector::Column2,
+            Selector::Row1,
+            Selector::Row2,
+        ] {
+            assert_eq!(sel.to_string().parse(), Ok(sel));
+            assert_eq!(sel.to_string().to_lowercase().parse(), Ok(sel));
+        }
+
+        assert_eq!("r2".parse(), Ok(Selector::Row2));
+        assert!("C3".parse::<Selector>().is_err());
+        assert!("".parse::<Selector>().is_err());
+    }
+
     #[test]
     fn activation_order_uniqueness() {
         assert_eq!(
@@ -551,6 +1183,234 @@ mod cell_tests {
         );
     }
 
+    #[test]
+    fn activation_order_every_combination() {
+        let lines = [
+            Selector::Column1,
+            Selector::Column2,
+            Selector::Row1,
+            Selector::Row2,
+        ];
+        let mut valid = 0;
+
+        for i in 0..256 {
+            let order = core::array::from_fn(|n| lines[(i >> (2 * n)) & 0b11]);
+            let distinct = (0..4).all(|a| (a + 1..4).all(|b| order[a] != order[b]));
+
+            match ActivationOrder::new(order) {
+                Ok(new) => {
+                    assert!(distinct, "{order:?}");
+                    assert_eq!(new, ActivationOrder(order));
+                    valid += 1;
+                }
+                Err(err) => {
+                    assert!(!distinct, "{order:?}");
+                    assert_eq!(err, "Duplicate enum variants not allowed");
+                }
+            }
+        }
+
+        // One for each permutation of the 4 lines.
+        assert_eq!(valid, 24);
+    }
+
+    #[test]
+    fn activation_order_position_of() {
+        let order = ActivationOrder([
+            Selector::Row2,
+            Selector::Column1,
+            Selector::Row1,
+            Selector::Column2,
+        ]);
+
+        assert_eq!(order.position_of(Selector::Row2), 0);
+        assert_eq!(order.position_of(Selector::Column1), 1);
+        assert_eq!(order.position_of(Selector::Row1), 2);
+        assert_eq!(order.position_of(Selector::Column2), 3);
+
+        for (i, sel) in ActivationOrder::default().into_iter().enumerate() {
+            assert_eq!(ActivationOrder::default().position_of(sel), i);
+        }
+    }
+
+    #[test]
+    fn activation_order_bytes() {
+        let order = ActivationOrder([
+            Selector::Row2,
+            Selector::Column1,
+            Selector::Row1,
+            Selector::Column2,
+        ]);
+        assert_eq!(<[u8; 4]>::from(order), [3, 0, 2, 1]);
+        assert_eq!(ActivationOrder::try_from([3, 0, 2, 1]), Ok(order));
+
+        for bytes in [[0; 4], [0, 1, 1, 3], [3, 2, 1, 3]] {
+            assert_eq!(
+                ActivationOrder::try_from(bytes),
+                Err("Duplicate enum variants not allowed")
+            );
+        }
+        for bytes in [[0, 1, 2, 4], [255, 0, 1, 2]] {
+            assert_eq!(
+                ActivationOrder::try_from(bytes),
+                Err("Line index out of range, expected 0 to 3")
+            );
+        }
+    }
+
+    #[test]
+    fn activation_order_deserialization() {
+        let order: ActivationOrder =
+            serde_json::from_str(r#"["Row1", "Column1", "Row2", "Column2"]"#).unwrap();
+        assert_eq!(
+            order,
+            ActivationOrder([
+                Selector::Row1,
+                Selector::Column1,
+                Selector::Row2,
+                Selector::Column2
+            ])
+        );
+
+        let duplicates =
+            serde_json::from_str::<ActivationOrder>(r#"["Row1", "Column1", "Row1", "Column2"]"#);
+        assert!(duplicates.is_err());
+
+        let bytes = postcard::to_allocvec(&[0u8, 0, 2, 3]).unwrap();
+        assert!(postcard::from_bytes::<ActivationOrder>(&bytes).is_err());
+
+        let bytes = postcard::to_allocvec(&ActivationOrder::default()).unwrap();
+        assert_eq!(
+            postcard::from_bytes::<ActivationOrder>(&bytes),
+            Ok(ActivationOrder::default())
+        );
+    }
+
+    #[test]
+    fn line_array_indexing() {
+        let mut lines = LineArray::new([1, 2, 3, 4]);
+        assert_eq!(lines[Selector::Column1], 1);
+        assert_eq!(lines[Selector::Row2], 4);
+
+        lines[Selector::Row1] = 7;
+        assert_eq!(lines.into_iter().collect::<Vec<_>>(), [1, 2, 7, 4]);
+
+        let mut fills = Fills::new(1, 2, 3, 4);
+        fills.set_line(Selector::Column2, 5);
+        assert_eq!(fills.get_line(Selector::Column2), 5);
+        assert_eq!(fills, Fills::from(LineArray::new([1, 5, 3, 4])));
+    }
+
+    #[test]
+    fn fills_serialization() {
+        let fills = Fills::new(1, 2, 3, 4);
+        // Serialized just like the plain array used to be.
+        assert_eq!(serde_json::to_string(&fills).unwrap(), "[1,2,3,4]");
+        assert_eq!(
+            postcard::to_allocvec(&fills).unwrap(),
+            postcard::to_allocvec(&[1u8, 2, 3, 4]).unwrap()
+        );
+        assert_eq!(serde_json::from_str::<Fills>("[1,2,3,4]").unwrap(), fills);
+    }
+
+    #[test]
+    fn cell_flags_deserialization() {
+        let still = CellFlags::STILL_C1 | CellFlags::STILL_C2 | CellFlags::STILL_R1;
+
+        // The serialized bits only contain JC1_R1 and NOT_C1.
+        let bytes = postcard::to_allocvec(&0b1_0001u16).unwrap();
+        let flags: CellFlags = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(flags, CellFlags::JC1_R1 | CellFlags::NOT_C1 | still);
+
+        let flags: CellFlags = serde_json::from_str(r#""JC2_R2 | C1_OUT""#).unwrap();
+        assert_eq!(flags, CellFlags::JC2_R2 | CellFlags::C1_OUT | still);
+
+        let flags = CellFlags::new_with_output(true, false, true, false, true, false);
+        let bytes = postcard::to_allocvec(&flags).unwrap();
+        assert_eq!(postcard::from_bytes::<CellFlags>(&bytes), Ok(flags));
+    }
+
+    #[test]
+    fn cell_flags_editable_names() {
+        assert_eq!(
+            CellFlags::from_editable_name("not_c1"),
+            Ok(CellFlags::NOT_C1)
+        );
+        assert_eq!(
+            CellFlags::from_editable_name("JC2_R1"),
+            Ok(CellFlags::JC2_R1)
+        );
+        assert!(CellFlags::from_editable_name("still_c1").is_err());
+        assert!(CellFlags::from_editable_name("NOT_R2").is_err());
+
+        assert!(!CellFlags::EDITABLE.intersects(CellFlags::SIMULATION));
+        assert_eq!(
+            CellFlags::EDITABLE | CellFlags::SIMULATION,
+            CellFlags::all()
+        );
+    }
+
+    #[test]
+    fn cell_flags_groups() {
+        let groups = [CellFlags::JUNCTIONS, CellFlags::OUTPUTS, CellFlags::NOTS];
+
+        for (i, group) in groups.iter().enumerate() {
+            assert!(!group.intersects(CellFlags::SIMULATION));
+            assert!(CellFlags::EDITABLE.contains(*group));
+            for other in &groups[i + 1..] {
+                assert!(!group.intersects(*other));
+            }
+        }
+        assert_eq!(
+            CellFlags::JUNCTIONS | CellFlags::OUTPUTS | CellFlags::NOTS | CellFlags::DISABLED,
+            CellFlags::EDITABLE
+        );
+
+        let flags =
+            CellFlags::default() | CellFlags::JC2_R1 | CellFlags::R2_OUT | CellFlags::NOT_C1;
+        assert_eq!(flags.junctions(), CellFlags::JC2_R1);
+        assert_eq!(flags.outputs(), CellFlags::R2_OUT);
+        assert_eq!(flags.nots(), CellFlags::NOT_C1);
+        assert!(CellFlags::default().outputs().is_empty());
+    }
+
+    #[test]
+    fn cell_flags_consistency() {
+        let valid = [
+            CellFlags::default(),
+            CellFlags::default() | CellFlags::DISABLED,
+            CellFlags::default() | CellFlags::JC1_R2 | CellFlags::C1_OUT | CellFlags::R2_OUT,
+            CellFlags::default() | CellFlags::NOT_C2 | CellFlags::C2_OUT | CellFlags::R1_OUT,
+            CellFlags::default() | CellFlags::JC2_R2 | CellFlags::NOT_C1 | CellFlags::OUTPUTS,
+        ];
+        for flags in valid {
+            assert_eq!(flags.check_consistency(), Ok(()), "{flags:?}");
+        }
+
+        assert_eq!(
+            CellFlags::empty().check_consistency(),
+            Err(vec!["STILL_C1, STILL_C2 and STILL_R1 must be set"])
+        );
+        assert_eq!(
+            (CellFlags::default() | CellFlags::OUTPUTS).check_consistency(),
+            Err(vec![
+                "C1_OUT needs a junction or NOT on Col 1",
+                "C2_OUT needs a junction or NOT on Col 2",
+                "R1_OUT needs a junction or NOT on Row 1",
+                "R2_OUT needs a junction on Row 2",
+            ])
+        );
+        // NOT_C1 only connects Col 1 and Row 1.
+        assert_eq!(
+            (CellFlags::default() | CellFlags::NOT_C1 | CellFlags::C2_OUT | CellFlags::R2_OUT)
+                .check_consistency(),
+            Err(vec![
+                "C2_OUT needs a junction or NOT on Col 2",
+                "R2_OUT needs a junction on Row 2",
+            ])
+        );
+    }
+
     #[test]
     fn column_evaluation_1() {
         let order = ActivationOrder::new([
@@ -604,8 +1464,6 @@ mod cell_tests {
             cell.eval_cell(input),
             CellIO::new(true, false, false, false)
         );
-
-        cell.print_truth_table();
     }
 
     // Self=1 gate
@@ -786,6 +1644,28 @@ mod cell_tests {
         assert_eq!(cell.eval_cell(input), CellIO::new(true, false, true, true));
     }
 
+    #[test]
+    fn disabled_cell_evaluation() {
+        let order = ActivationOrder::default();
+
+        // Without DISABLED this cell outputs C1 and R1 for any input.
+        let mut flags = CellFlags::new_with_output(true, false, false, false, false, false);
+        let fills = Fills::new(4, 0, 4, 0);
+
+        let cell = Cell::new(&order, &flags, fills);
+        assert_eq!(
+            cell.eval_cell(CellIO::empty()),
+            CellIO::COLUMN_1 | CellIO::ROW_1
+        );
+
+        flags.set(CellFlags::DISABLED, true);
+        let cell = Cell::new(&order, &flags, fills);
+
+        for input in CellIO::combinations() {
+            assert_eq!(cell.eval_cell(input), input);
+        }
+    }
+
     #[test]
     fn not_column_evaluation_1() {
         let order = ActivationOrder::new([
@@ -869,4 +1749,267 @@ mod cell_tests {
 
         assert_eq!(cell.eval_cell(input), CellIO::new(true, false, true, true));
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn cell_equality_and_hashing() {
+        use std::collections::HashMap;
+
+        let order = ActivationOrder::new([
+            Selector::Row1,
+            Selector::Column1,
+            Selector::Row2,
+            Selector::Column2,
+        ])
+        .unwrap();
+
+        let flags = CellFlags::new_with_output(true, false, false, false, true, false);
+
+        let a = Cell::new(&order, &flags, Fills::new(1, 0, 1, 2));
+        let b = Cell::new(&order, &flags, Fills::new(1, 0, 1, 2));
+        let c = Cell::new(&order, &flags, Fills::new(0, 0, 1, 2));
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(Cell::default(), Cell::default());
+
+        let mut distinct: HashMap<Cell, usize> = HashMap::new();
+        for cell in [a, b, c, Cell::default()] {
+            *distinct.entry(cell).or_default() += 1;
+        }
+
+        assert_eq!(distinct.len(), 3);
+        assert_eq!(distinct[&a], 2);
+    }
+
+    #[test]
+    fn reset_sim_flags() {
+        let order = ActivationOrder::default();
+        let cell = Cell::new(
+            &order,
+            &(CellFlags::JC1_R1 | CellFlags::R1_OUT),
+            Fills::new(1, 0, 2, 0),
+        );
+
+        let mut broken = cell;
+        broken
+            .flags
+            .remove(CellFlags::STILL_C2 | CellFlags::STILL_R1);
+        assert!(!broken.check().is_empty());
+
+        broken.reset_sim_flags();
+        assert_eq!(broken, cell);
+        assert!(broken.check().is_empty());
+
+        // Nothing changes on a cell that's already fine.
+        broken.reset_sim_flags();
+        assert_eq!(broken, cell);
+    }
+
+    #[test]
+    fn remap_lines() {
+        let cell = Cell::new(
+            &ActivationOrder::new([
+                Selector::Row2,
+                Selector::Column1,
+                Selector::Row1,
+                Selector::Column2,
+            ])
+            .unwrap(),
+            &(CellFlags::JC1_R2 | CellFlags::C2_OUT | CellFlags::NOT_C1 | CellFlags::DISABLED),
+            Fills::new(1, 2, 3, 4),
+        );
+        let swap_columns = |sel| match sel {
+            Selector::Column1 => Selector::Column2,
+            Selector::Column2 => Selector::Column1,
+            row => row,
+        };
+
+        let swapped = cell.remap_lines(swap_columns).unwrap();
+        assert_eq!(
+            swapped,
+            Cell::new(
+                &ActivationOrder::new([
+                    Selector::Row2,
+                    Selector::Column2,
+                    Selector::Row1,
+                    Selector::Column1,
+                ])
+                .unwrap(),
+                &(CellFlags::JC2_R2 | CellFlags::C1_OUT | CellFlags::NOT_C2 | CellFlags::DISABLED),
+                Fills::new(2, 1, 3, 4),
+            )
+        );
+        assert_eq!(swapped.remap_lines(swap_columns), Ok(cell));
+
+        let transpose = |sel| match sel {
+            Selector::Column1 => Selector::Row1,
+            Selector::Column2 => Selector::Row2,
+            Selector::Row1 => Selector::Column1,
+            Selector::Row2 => Selector::Column2,
+        };
+        assert_eq!(
+            cell.remap_lines(transpose),
+            Err("Only columns can be negated")
+        );
+        let flip_rows = |sel| match sel {
+            Selector::Row1 => Selector::Row2,
+            Selector::Row2 => Selector::Row1,
+            column => column,
+        };
+        assert_eq!(
+            cell.remap_lines(flip_rows),
+            Err("The NOTs always feed Row 1")
+        );
+
+        let mut junction = Cell::default();
+        junction.flags |= CellFlags::JC1_R1;
+        assert_eq!(
+            junction.remap_lines(|sel| match sel {
+                Selector::Row1 => Selector::Column2,
+                Selector::Column2 => Selector::Row1,
+                other => other,
+            }),
+            Err("A junction must connect a column and a row")
+        );
+        assert!(junction.remap_lines(|_| Selector::Row1).is_err());
+    }
+
+    #[test]
+    fn remap_lines_keeps_the_behavior() {
+        let maps: [fn(Selector) -> Selector; 3] = [
+            // Transpose.
+            |sel| match sel {
+                Selector::Column1 => Selector::Row1,
+                Selector::Column2 => Selector::Row2,
+                Selector::Row1 => Selector::Column1,
+                Selector::Row2 => Selector::Column2,
+            },
+            // Horizontal flip.
+            |sel| match sel {
+                Selector::Column1 => Selector::Column2,
+                Selector::Column2 => Selector::Column1,
+                row => row,
+            },
+            // Vertical flip.
+            |sel| match sel {
+                Selector::Row1 => Selector::Row2,
+                Selector::Row2 => Selector::Row1,
+                column => column,
+            },
+        ];
+        let lines = [
+            Selector::Column1,
+            Selector::Column2,
+            Selector::Row1,
+            Selector::Row2,
+        ];
+        let remap_io = |map: fn(Selector) -> Selector, io: CellIO| {
+            lines
+                .into_iter()
+                .filter(|line| io.contains(CellIO::line(*line)))
+                .fold(CellIO::empty(), |mapped, line| {
+                    mapped | CellIO::line(map(line))
+                })
+        };
+
+        let mut rng = crate::tests::Rng(0x5851_F42D_4C95_7F2D);
+        for _ in 0..500 {
+            let cell = rng.cell();
+            for map in maps {
+                let Ok(remapped) = cell.remap_lines(map) else {
+                    continue;
+                };
+                for input in CellIO::combinations() {
+                    assert_eq!(
+                        remapped.eval_cell(remap_io(map, input)),
+                        remap_io(map, cell.eval_cell(input)),
+                        "{cell:?} on {input:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sim_params() {
+        let default = SimParams::default();
+        assert_eq!((default.fixed_blocks, default.threshold), (9, 12));
+
+        let flags = CellFlags::new_with_output(true, false, false, true, true, false);
+        let cell = Cell::new(&ActivationOrder::default(), &flags, Fills::new(0, 1, 2, 0));
+        for input in CellIO::combinations() {
+            assert_eq!(cell.eval_cell_with(input, &default), cell.eval_cell(input));
+        }
+
+        // Every line of a blank cell is above a threshold lower
+        // than its fixed blocks, and none reaches the highest one.
+        let always = SimParams {
+            fixed_blocks: 9,
+            threshold: 8,
+        };
+        let never = SimParams {
+            fixed_blocks: 0,
+            threshold: u8::MAX,
+        };
+        for input in CellIO::combinations() {
+            assert_eq!(
+                Cell::default().eval_cell_with(input, &always),
+                CellIO::all()
+            );
+            assert_eq!(
+                Cell::default().eval_cell_with(input, &never),
+                CellIO::empty()
+            );
+        }
+    }
+
+    #[test]
+    fn eval_cell_is_pure() {
+        let mut rng = crate::tests::Rng(0xBF58_476D_1CE4_E5B9);
+
+        for _ in 0..200 {
+            let cell = rng.cell();
+            let original = cell;
+
+            for input in CellIO::combinations() {
+                let (output, fired) = cell.eval_cell_verbose(input);
+                assert_eq!(output, cell.eval_cell(input));
+                assert!(cell.flags.contains(fired));
+            }
+
+            for input in CellIO::combinations() {
+                let output = cell.eval_cell(input);
+                assert_eq!(cell.eval_cell(input), output);
+                assert_eq!(cell, original);
+            }
+            assert_eq!(
+                cell.truth_table(),
+                core::array::from_fn(|i| original.eval_cell(CellIO::from_bits_truncate(i as u8)))
+            );
+        }
+    }
+
+    #[test]
+    fn eval_cell_verbose() {
+        // Row 1 copies Col 1 through their junction.
+        let mut cell = Cell::default();
+        cell.flags |= CellFlags::JC1_R1 | CellFlags::R1_OUT;
+        cell.fills = Fills::new(2, 0, 2, 0);
+
+        let (output, fired) = cell.eval_cell_verbose(CellIO::COLUMN_1);
+        assert_eq!(output, CellIO::COLUMN_1 | CellIO::ROW_1);
+        assert_eq!(fired, CellFlags::JC1_R1 | CellFlags::R1_OUT);
+
+        // Col 1 doesn't fire, clearing the junction for Row 1.
+        let (output, fired) = cell.eval_cell_verbose(CellIO::empty());
+        assert_eq!(output, CellIO::empty());
+        assert_eq!(fired, CellFlags::empty());
+
+        cell.flags |= CellFlags::DISABLED;
+        assert_eq!(
+            cell.eval_cell_verbose(CellIO::COLUMN_1),
+            (CellIO::COLUMN_1, CellFlags::empty())
+        );
+    }
 }
@@ -0,0 +1,129 @@
+//! This module contains a precomputed version of the [FPGA],
+//! where each [Cell](crate::cell::Cell) is replaced by its
+//! truth table.
+//!
+//! Since a cell only has 4 inputs its whole behaviour fits in
+//! 16 [CellIO]s, which turns every cell evaluation of a sweep
+//! into a single array lookup.
+
+use crate::cell::CellIO;
+use crate::{EvalError, FPGA, FpgaIO, ScanPattern, sweep};
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::ops::ControlFlow;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledFpga {
+    width: usize,
+    height: usize,
+    // The truth table of each cell, in row-major order.
+    tables: Vec<[CellIO; 16]>,
+}
+
+impl CompiledFpga {
+    #[inline]
+    pub fn new(fpga: &FPGA) -> Self {
+        Self {
+            width: fpga.width(),
+            height: fpga.height(),
+            tables: fpga.cells().map(|cell| cell.truth_table()).collect(),
+        }
+    }
+
+    /// Rebuilds a [CompiledFpga] out of the tables of a previous
+    /// one, see [CompiledFpga::tables], without evaluating any cell.
+    ///
+    /// ## Returns
+    ///
+    /// - [Err()] if there aren't exactly `width * height` tables,
+    ///   or if `width * height` overflows.
+    #[inline]
+    pub fn from_tables(
+        width: usize,
+        height: usize,
+        tables: Vec<[CellIO; 16]>,
+    ) -> Result<Self, &'static str> {
+        if width.checked_mul(height) != Some(tables.len()) {
+            return Err("Table count does not match FPGA dimensions");
+        }
+
+        Ok(Self {
+            width,
+            height,
+            tables,
+        })
+    }
+
+    /// The truth table of each cell, in row-major order.
+    #[inline]
+    pub fn tables(&self) -> &[[CellIO; 16]] {
+        &self.tables
+    }
+
+    /// Checks that these are the tables of `fpga`, computing
+    /// them again and comparing them one by one.
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok(())] if they match.
+    /// - [Err(Vec<String>)] with the mismatched dimensions or
+    ///   every cell whose table differs otherwise.
+    pub fn validate(&self, fpga: &FPGA) -> Result<(), Vec<String>> {
+        if self.dimensions() != fpga.dimensions() {
+            return Err(vec![format!(
+                "Compiled for a {}x{} FPGA, found a {}x{} one",
+                self.width,
+                self.height,
+                fpga.width(),
+                fpga.height()
+            )]);
+        }
+
+        let problems: Vec<String> = fpga
+            .cells()
+            .zip(&self.tables)
+            .enumerate()
+            .filter(|(_, (cell, table))| cell.truth_table() != **table)
+            .map(|(i, _)| {
+                let (row, col) = (i / self.width, i % self.width);
+                format!("Cell ({row}, {col}): the table doesn't match the cell")
+            })
+            .collect();
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Evaluates the board exactly like [FPGA::eval].
+    #[inline]
+    pub fn eval(&self, input: FpgaIO) -> Result<FpgaIO, EvalError> {
+        sweep(
+            self.width,
+            self.height,
+            input,
+            ScanPattern::Boustrophedon,
+            |row, col, cell_io| self.tables[row * self.width + col][cell_io.bits() as usize],
+            |_, _, _| ControlFlow::Continue(()),
+        )
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the `(width, height)` of the compiled board.
+    #[inline]
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+}
@@ -0,0 +1,96 @@
+//! This module compares two [FPGA]s cell by cell,
+//! which is mostly useful to review design changes.
+
+use crate::FPGA;
+use crate::cell::Cell;
+use alloc::vec::Vec;
+use bitflags::bitflags;
+
+bitflags! {
+    /// This represents which parts of a [Cell] differ
+    /// between two [FPGA]s.
+    #[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
+    pub struct CellDiff: u8 {
+        const ACTIVATION_ORDER = 1 << 0;
+        const FLAGS = 1 << 1;
+        const FILLS = 1 << 2;
+    }
+}
+
+impl CellDiff {
+    /// Compares the single parts of the two cells.
+    #[inline]
+    pub fn new(a: &Cell, b: &Cell) -> Self {
+        let mut diff = CellDiff::empty();
+        diff.set(
+            CellDiff::ACTIVATION_ORDER,
+            a.activation_order != b.activation_order,
+        );
+        diff.set(CellDiff::FLAGS, a.flags != b.flags);
+        diff.set(CellDiff::FILLS, a.fills != b.fills);
+        diff
+    }
+}
+
+/// Compares `a` and `b` cell by cell.
+///
+/// ## Returns
+///
+/// - [Ok(Vec)] with the `(row, col)` of every differing cell
+///   and what differs in it, in row-major order.
+/// - [Err()] if the two FPGAs have different dimensions.
+pub fn diff(a: &FPGA, b: &FPGA) -> Result<Vec<(usize, usize, CellDiff)>, &'static str> {
+    if a.width() != b.width() || a.height() != b.height() {
+        return Err("FPGA dimensions differ");
+    }
+
+    let width = a.width();
+
+    Ok(a.cells()
+        .zip(b.cells())
+        .enumerate()
+        .filter_map(|(i, (a, b))| {
+            let diff = CellDiff::new(a, b);
+            (!diff.is_empty()).then_some((i / width, i % width, diff))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::{ActivationOrder, CellFlags, Fills, Selector};
+    use alloc::vec;
+
+    #[test]
+    fn diff_cells() {
+        let a = FPGA::new(3, 2);
+        let mut b = a.clone();
+
+        assert_eq!(diff(&a, &b), Ok(vec![]));
+
+        b.get_mut(0, 2).unwrap().flags.set(CellFlags::NOT_C1, true);
+        let cell = b.get_mut(1, 0).unwrap();
+        cell.fills = Fills::new(1, 0, 0, 0);
+        cell.activation_order = ActivationOrder::new([
+            Selector::Row1,
+            Selector::Row2,
+            Selector::Column1,
+            Selector::Column2,
+        ])
+        .unwrap();
+
+        assert_eq!(
+            diff(&a, &b),
+            Ok(vec![
+                (0, 2, CellDiff::FLAGS),
+                (1, 0, CellDiff::ACTIVATION_ORDER | CellDiff::FILLS),
+            ])
+        );
+    }
+
+    #[test]
+    fn diff_dimensions() {
+        assert!(diff(&FPGA::new(3, 2), &FPGA::new(2, 3)).is_err());
+    }
+}
@@ -0,0 +1,267 @@
+//! A C interface to load an [FPGA] and evaluate it.
+//!
+//! Both the [FPGA] and the [FpgaIO] are handed out as opaque
+//! pointers, owned by the caller until given back to their
+//! `_free` function. Every fallible function returns a [Status]
+//! and writes its result through its last argument.
+//!
+//! The crate is built as an `rlib`, a library usable from C
+//! is made with:
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! ```
+
+use crate::{EvalError, FPGA, FpgaIO};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::slice;
+
+/// The outcome of the functions of this module.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok = 0,
+    /// A required pointer was null.
+    NullPointer = 1,
+    /// The bytes aren't an FPGA made by [FPGA::to_bytes].
+    MalformedFpga = 2,
+    /// The amount of bits doesn't match, see [EvalError::SizeMismatch].
+    SizeMismatch = 3,
+    /// See [EvalError::EmptyGrid].
+    EmptyGrid = 4,
+}
+
+/// Decodes `len` bytes made by [FPGA::to_bytes] into a new
+/// [FPGA], written to `out` when [Status::Ok] is returned.
+///
+/// # Safety
+///
+/// `bytes` must point to `len` readable bytes and `out`
+/// must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gb_fpga_from_bytes(
+    bytes: *const u8,
+    len: usize,
+    out: *mut *mut FPGA,
+) -> Status {
+    if bytes.is_null() || out.is_null() {
+        return Status::NullPointer;
+    }
+
+    // SAFETY: the caller guarantees `len` readable bytes.
+    let bytes = unsafe { slice::from_raw_parts(bytes, len) };
+    match FPGA::try_from(bytes) {
+        Ok(fpga) => {
+            // SAFETY: `out` was checked and is valid for writes.
+            unsafe { out.write(Box::into_raw(Box::new(fpga))) };
+            Status::Ok
+        }
+        Err(_) => Status::MalformedFpga,
+    }
+}
+
+/// Frees an [FPGA] made by [gb_fpga_from_bytes], doing
+/// nothing if `fpga` is null.
+///
+/// # Safety
+///
+/// `fpga` must come from [gb_fpga_from_bytes] and
+/// not have been freed already.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gb_fpga_free(fpga: *mut FPGA) {
+    if !fpga.is_null() {
+        // SAFETY: the caller guarantees it's an owned FPGA.
+        drop(unsafe { Box::from_raw(fpga) });
+    }
+}
+
+/// The number of input bits [gb_fpga_eval] expects, 2 for each
+/// column, or 0 if `fpga` is null.
+///
+/// # Safety
+///
+/// `fpga` must be null or a live [FPGA].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gb_fpga_input_len(fpga: *const FPGA) -> usize {
+    // SAFETY: the caller guarantees it's null or live.
+    unsafe { fpga.as_ref() }.map_or(0, |fpga| fpga.width() * 2)
+}
+
+/// Evaluates `fpga` with the `len` bits of `input`, one per
+/// byte with any non-zero byte being set, writing the new
+/// [FpgaIO] holding the output to `out`.
+///
+/// # Safety
+///
+/// `fpga` must be a live [FPGA], `input` must point to `len`
+/// readable bytes and `out` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gb_fpga_eval(
+    fpga: *const FPGA,
+    input: *const u8,
+    len: usize,
+    out: *mut *mut FpgaIO,
+) -> Status {
+    // SAFETY: the caller guarantees it's null or live.
+    let Some(fpga) = (unsafe { fpga.as_ref() }) else {
+        return Status::NullPointer;
+    };
+    if input.is_null() || out.is_null() {
+        return Status::NullPointer;
+    }
+
+    // SAFETY: the caller guarantees `len` readable bytes.
+    let input = unsafe { slice::from_raw_parts(input, len) };
+    let input: Vec<bool> = input.iter().map(|&bit| bit != 0).collect();
+    match fpga.eval(FpgaIO::from(input.into_boxed_slice())) {
+        Ok(output) => {
+            // SAFETY: `out` was checked and is valid for writes.
+            unsafe { out.write(Box::into_raw(Box::new(output))) };
+            Status::Ok
+        }
+        Err(EvalError::EmptyGrid) => Status::EmptyGrid,
+        // Without probes or cancellation it's the only other error.
+        Err(_) => Status::SizeMismatch,
+    }
+}
+
+/// The number of column bits of `io`, or 0 if it's null.
+///
+/// # Safety
+///
+/// `io` must be null or a live [FpgaIO].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gb_fpga_io_len(io: *const FpgaIO) -> usize {
+    // SAFETY: the caller guarantees it's null or live.
+    unsafe { io.as_ref() }.map_or(0, |io| io.iter_bits().count())
+}
+
+/// Copies the column bits of `io` to `bits`, one per byte set
+/// to 0 or 1, which must hold exactly [gb_fpga_io_len] of them.
+///
+/// # Safety
+///
+/// `io` must be a live [FpgaIO] and `bits` must point
+/// to `len` writable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gb_fpga_io_bits(io: *const FpgaIO, bits: *mut u8, len: usize) -> Status {
+    // SAFETY: the caller guarantees it's null or live.
+    let Some(io) = (unsafe { io.as_ref() }) else {
+        return Status::NullPointer;
+    };
+    if bits.is_null() {
+        return Status::NullPointer;
+    }
+    if io.iter_bits().count() != len {
+        return Status::SizeMismatch;
+    }
+
+    // SAFETY: the caller guarantees `len` writable bytes.
+    let bits = unsafe { slice::from_raw_parts_mut(bits, len) };
+    for (byte, bit) in bits.iter_mut().zip(io.iter_bits()) {
+        *byte = bit as u8;
+    }
+    Status::Ok
+}
+
+/// Frees an [FpgaIO] made by [gb_fpga_eval], doing
+/// nothing if `io` is null.
+///
+/// # Safety
+///
+/// `io` must come from [gb_fpga_eval] and not
+/// have been freed already.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gb_fpga_io_free(io: *mut FpgaIO) {
+    if !io.is_null() {
+        // SAFETY: the caller guarantees it's an owned FpgaIO.
+        drop(unsafe { Box::from_raw(io) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::{CellFlags, Fills};
+    use core::ptr;
+
+    #[test]
+    fn eval_through_ffi() {
+        let mut fpga = FPGA::new(2, 1);
+        let cell = fpga.get_mut(0, 1).unwrap();
+        cell.flags |= CellFlags::C1_OUT;
+        cell.fills = Fills::new(3, 0, 0, 0);
+        let bytes = fpga.to_bytes();
+        let input = [0u8, 0, 1, 0];
+
+        unsafe {
+            let mut handle = ptr::null_mut();
+            assert_eq!(
+                gb_fpga_from_bytes(bytes.as_ptr(), bytes.len(), &mut handle),
+                Status::Ok
+            );
+            assert_eq!(gb_fpga_input_len(handle), 4);
+
+            let mut output = ptr::null_mut();
+            assert_eq!(
+                gb_fpga_eval(handle, input.as_ptr(), input.len(), &mut output),
+                Status::Ok
+            );
+            assert_eq!(gb_fpga_io_len(output), 4);
+
+            let mut bits = [0xFF; 4];
+            assert_eq!(
+                gb_fpga_io_bits(output, bits.as_mut_ptr(), bits.len()),
+                Status::Ok
+            );
+            let expected = fpga.eval(FpgaIO::from_u64(4, 0b0100)).unwrap();
+            assert_eq!(bits.map(|bit| bit == 1), *expected.get_value_vec());
+            assert_eq!(
+                gb_fpga_io_bits(output, bits.as_mut_ptr(), 3),
+                Status::SizeMismatch
+            );
+
+            gb_fpga_io_free(output);
+            gb_fpga_free(handle);
+        }
+    }
+
+    #[test]
+    fn ffi_errors() {
+        let bytes = FPGA::new(2, 1).to_bytes();
+
+        unsafe {
+            let mut handle = ptr::null_mut();
+            assert_eq!(
+                gb_fpga_from_bytes(ptr::null(), 0, &mut handle),
+                Status::NullPointer
+            );
+            assert_eq!(
+                gb_fpga_from_bytes(bytes.as_ptr(), bytes.len() - 1, &mut handle),
+                Status::MalformedFpga
+            );
+            assert!(handle.is_null());
+
+            assert_eq!(
+                gb_fpga_from_bytes(bytes.as_ptr(), bytes.len(), &mut handle),
+                Status::Ok
+            );
+            let mut output = ptr::null_mut();
+            assert_eq!(
+                gb_fpga_eval(handle, [1u8; 3].as_ptr(), 3, &mut output),
+                Status::SizeMismatch
+            );
+            assert_eq!(
+                gb_fpga_eval(ptr::null(), [1u8; 4].as_ptr(), 4, &mut output),
+                Status::NullPointer
+            );
+            assert!(output.is_null());
+            assert_eq!(gb_fpga_input_len(ptr::null()), 0);
+
+            gb_fpga_free(handle);
+            gb_fpga_free(ptr::null_mut());
+            gb_fpga_io_free(ptr::null_mut());
+        }
+    }
+}
@@ -1,11 +1,33 @@
-use crate::cell::{Cell, CellIO};
+// Without the `std` feature only `alloc` is needed.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use crate::cell::{Cell, CellFlags, CellIO, Selector};
+pub use crate::compiled::CompiledFpga;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+use core::fmt::Write;
+use core::ops::ControlFlow;
+use core::str::FromStr;
 use serde::{Deserialize, Serialize};
 
 #[allow(unused)]
 pub mod cell;
+pub mod compiled;
+pub mod diff;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod macros;
+pub mod schematic;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct FPGA {
     // Width of the FPGA, this is the number of columns
     width: usize,
@@ -27,6 +49,46 @@ impl FPGA {
         }
     }
 
+    /// Builds an [FPGA] out of already existing cells, laid
+    /// out in row-major order.
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok(FPGA)] if `data` contains exactly `width * height` cells.
+    /// - [Err()] otherwise, or if `width * height` overflows.
+    #[inline]
+    pub fn from_cells(width: usize, height: usize, data: Vec<Cell>) -> Result<Self, &'static str> {
+        if width.checked_mul(height) != Some(data.len()) {
+            return Err("Cell count does not match FPGA dimensions");
+        }
+
+        Ok(Self {
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// Iterates over all the cells in row-major order.
+    #[inline]
+    pub fn cells(&self) -> impl Iterator<Item = &Cell> {
+        self.data.iter()
+    }
+
+    /// Folds over all the cells in row-major order, like calling
+    /// [Iterator::fold] on [FPGA::cells].
+    #[inline]
+    pub fn fold_cells<T>(&self, init: T, f: impl FnMut(T, &Cell) -> T) -> T {
+        self.data.iter().fold(init, f)
+    }
+
+    /// Counts the cells whose configuration differs from
+    /// the default one, see [Cell::is_default].
+    #[inline]
+    pub fn used_cell_count(&self) -> usize {
+        self.data.iter().filter(|cell| !cell.is_default()).count()
+    }
+
     #[inline]
     pub fn get_cell(&self, row: usize, col: usize) -> Option<&Cell> {
         if row < self.height && col < self.width {
@@ -36,6 +98,27 @@ impl FPGA {
         }
     }
 
+    /// Returns all the cells of the given row.
+    #[inline]
+    pub fn row(&self, r: usize) -> Option<&[Cell]> {
+        if r < self.height {
+            Some(&self.data[r * self.width..(r + 1) * self.width])
+        } else {
+            None
+        }
+    }
+
+    /// Returns all the cells of the given column, from
+    /// the first row to the last one.
+    #[inline]
+    pub fn column(&self, c: usize) -> Option<Vec<&Cell>> {
+        if c < self.width {
+            Some(self.data.iter().skip(c).step_by(self.width).collect())
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut Cell> {
         if row < self.height && col < self.width {
@@ -45,31 +128,515 @@ impl FPGA {
         }
     }
 
-    #[inline]
-    pub fn eval(&self, mut input: FpgaIO) -> Result<FpgaIO, &'static str> {
-        if input.len() * 8 + input.trim as usize - 2 != self.width * 2 {
-            return Err("FpgaIO size does not match grid input requirements");
+    /// Copies the whole configuration of the `from` cell
+    /// into the `to` cell, both given as `(row, col)`.
+    ///
+    /// ## Returns
+    ///
+    /// - [Err()] without changing anything if either cell
+    ///   is out of bounds.
+    pub fn copy_cell(
+        &mut self,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Result<(), &'static str> {
+        let cell = *self
+            .get_cell(from.0, from.1)
+            .ok_or("Source cell out of bounds")?;
+        *self
+            .get_mut(to.0, to.1)
+            .ok_or("Target cell out of bounds")? = cell;
+
+        Ok(())
+    }
+
+    /// Sets every cell of the region going from `top_left` to
+    /// `bottom_right`, both `(row, col)` and inclusive, to `cell`.
+    ///
+    /// ## Returns
+    ///
+    /// - [Err()] without changing anything if the region is
+    ///   reversed or not fully inside the FPGA.
+    pub fn fill_region(
+        &mut self,
+        top_left: (usize, usize),
+        bottom_right: (usize, usize),
+        cell: Cell,
+    ) -> Result<(), &'static str> {
+        if top_left.0 > bottom_right.0 || top_left.1 > bottom_right.1 {
+            return Err("Region corners are reversed");
+        }
+        if bottom_right.0 >= self.height || bottom_right.1 >= self.width {
+            return Err("Region out of bounds");
         }
 
-        let mut i = 0;
-        let mut j = 0;
-        let mut dir: i8 = 1;
+        for row in top_left.0..=bottom_right.0 {
+            let start = row * self.width;
+            self.data[start + top_left.1..=start + bottom_right.1].fill(cell);
+        }
 
-        for _ in 0..self.height * (self.width) {
-            let cell_io = self.get_cell(j, i).unwrap().eval_cell(input.cell_io_at(i));
+        Ok(())
+    }
 
-            input.set(i, cell_io);
+    /// Calls `f` with the `(row, col)` of every cell and the cell
+    /// itself, in row-major order, so that it can be edited in place.
+    ///
+    /// The dimensions never change, only the cells do.
+    pub fn map_cells(&mut self, mut f: impl FnMut(usize, usize, &mut Cell)) {
+        let width = self.width;
+        for (i, cell) in self.data.iter_mut().enumerate() {
+            f(i / width, i % width, cell);
+        }
+    }
+
+    /// Calls `f` on every cell of `region`, given as `(row, col)`,
+    /// to edit them all at once, e.g. to set a flag on each.
+    ///
+    /// Whatever `f` does to the [CellFlags::SIMULATION] flags is
+    /// undone, and the board is normalized afterwards.
+    ///
+    /// ## Returns
+    ///
+    /// - [Err()] without changing anything if a cell of the
+    ///   region is out of bounds.
+    pub fn apply_to_region(
+        &mut self,
+        region: impl IntoIterator<Item = (usize, usize)>,
+        mut f: impl FnMut(&mut Cell),
+    ) -> Result<(), &'static str> {
+        let region: Vec<(usize, usize)> = region.into_iter().collect();
+        if region
+            .iter()
+            .any(|&(row, col)| row >= self.height || col >= self.width)
+        {
+            return Err("Region out of bounds");
+        }
+
+        for (row, col) in region {
+            let cell = &mut self.data[row * self.width + col];
+            let simulation = cell.flags.intersection(CellFlags::SIMULATION);
+            f(cell);
+            cell.flags = cell.flags.difference(CellFlags::SIMULATION) | simulation;
+        }
+        self.normalize();
+
+        Ok(())
+    }
 
-            if (i == self.width - 1 && dir == 1) || i == 0 && dir == -1 {
-                dir *= -1;
-                j += 1;
-                input.reset_row_io();
+    /// Restores the simulation invariants of every cell with
+    /// [Cell::reset_sim_flags], after loading an untrusted board
+    /// or editing its cells in bulk.
+    pub fn normalize(&mut self) {
+        self.data.iter_mut().for_each(Cell::reset_sim_flags);
+    }
+
+    /// Swaps the rows and the columns of the board: the cell at
+    /// `(row, col)` moves to `(col, row)` and its columns become
+    /// its rows, see [Cell::remap_lines].
+    ///
+    /// The sweep still goes through the rows from the first one, so
+    /// the transposed board generally computes something else.
+    ///
+    /// ## Returns
+    ///
+    /// - [Err()] without changing anything if a cell has a NOT,
+    ///   since it would end up on a row.
+    pub fn transpose(&mut self) -> Result<(), &'static str> {
+        self.transform(
+            self.height,
+            self.width,
+            |row, col| (col, row),
+            |sel| match sel {
+                Selector::Column1 => Selector::Row1,
+                Selector::Column2 => Selector::Row2,
+                Selector::Row1 => Selector::Column1,
+                Selector::Row2 => Selector::Column2,
+            },
+        )
+    }
+
+    /// Turns the board clockwise by a quarter, as the viewer
+    /// draws it with the first row at the bottom. It's a
+    /// [FPGA::transpose] followed by a [FPGA::flip_vertical],
+    /// so four of them give back the same board.
+    ///
+    /// Boards with a NOT can't be turned, not even by a full turn
+    /// of four calls: a cell only negates its columns, which the
+    /// first quarter turn would make rows, see [Cell::remap_lines].
+    ///
+    /// ## Returns
+    ///
+    /// - [Err()] without changing anything if a cell has a NOT.
+    pub fn rotate_90(&mut self) -> Result<(), &'static str> {
+        self.transpose()?;
+        self.flip_vertical()
+    }
+
+    /// Mirrors the board left to right, swapping Col 1 and
+    /// Col 2 of every cell.
+    pub fn flip_horizontal(&mut self) {
+        let width = self.width;
+        self.transform(
+            self.width,
+            self.height,
+            |row, col| (row, width - 1 - col),
+            |sel| match sel {
+                Selector::Column1 => Selector::Column2,
+                Selector::Column2 => Selector::Column1,
+                row => row,
+            },
+        )
+        .expect("Swapping the columns keeps the NOTs on a column");
+    }
+
+    /// Mirrors the board top to bottom, swapping Row 1 and
+    /// Row 2 of every cell.
+    ///
+    /// ## Returns
+    ///
+    /// - [Err()] without changing anything if a cell has a NOT,
+    ///   since it would no longer feed the same row.
+    pub fn flip_vertical(&mut self) -> Result<(), &'static str> {
+        let height = self.height;
+        self.transform(
+            self.width,
+            self.height,
+            |row, col| (height - 1 - row, col),
+            |sel| match sel {
+                Selector::Row1 => Selector::Row2,
+                Selector::Row2 => Selector::Row1,
+                column => column,
+            },
+        )
+    }
+
+    /// Rebuilds the board as `width` x `height`, the cell at `(row, col)`
+    /// coming from the position returned by `source` with its lines
+    /// remapped by `lines`. Nothing changes if a cell can't be remapped.
+    fn transform(
+        &mut self,
+        width: usize,
+        height: usize,
+        source: impl Fn(usize, usize) -> (usize, usize),
+        lines: fn(Selector) -> Selector,
+    ) -> Result<(), &'static str> {
+        let mut data = Vec::with_capacity(width * height);
+        for row in 0..height {
+            for col in 0..width {
+                let (row, col) = source(row, col);
+                data.push(self.data[row * self.width + col].remap_lines(lines)?);
+            }
+        }
+
+        *self = FPGA {
+            width,
+            height,
+            data,
+        };
+        Ok(())
+    }
+
+    #[inline]
+    pub fn eval(&self, input: FpgaIO) -> Result<FpgaIO, EvalError> {
+        self.sweep(input, |_, _, _| ControlFlow::Continue(()))
+    }
+
+    /// Evaluates the FPGA like [FPGA::eval], but going through
+    /// the cells of each row in the order given by `pattern`.
+    pub fn eval_with_pattern(
+        &self,
+        input: FpgaIO,
+        pattern: ScanPattern,
+    ) -> Result<FpgaIO, EvalError> {
+        sweep(
+            self.width,
+            self.height,
+            input,
+            pattern,
+            |row, col, cell_io| self.data[row * self.width + col].eval_cell(cell_io),
+            |_, _, _| ControlFlow::Continue(()),
+        )
+    }
+
+    /// Evaluates the FPGA like [FPGA::eval], calling `on_row` with
+    /// the amount of completed rows after each one of them.
+    ///
+    /// Returning [ControlFlow::Break] from `on_row` stops the
+    /// evaluation with [EvalError::Cancelled].
+    pub fn eval_with_progress(
+        &self,
+        input: FpgaIO,
+        mut on_row: impl FnMut(usize) -> ControlFlow<()>,
+    ) -> Result<FpgaIO, EvalError> {
+        let mut evaluated = 0;
+
+        self.sweep(input, |_, _, _| {
+            evaluated += 1;
+            if evaluated % self.width == 0 {
+                on_row(evaluated / self.width)
             } else {
-                i = (i as isize + dir as isize) as usize;
+                ControlFlow::Continue(())
             }
+        })
+    }
+
+    /// Evaluates the FPGA like [FPGA::eval] while also recording
+    /// the output [CellIO] of each probed `(row, col)` cell at the
+    /// moment it gets evaluated.
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok((FpgaIO, Vec<CellIO>))] with the probe values in the
+    ///   same order as `probes`.
+    /// - [Err(EvalError)] if `input` doesn't match the FPGA or any
+    ///   probe is out of bounds.
+    pub fn eval_with_probes(
+        &self,
+        input: FpgaIO,
+        probes: &[(usize, usize)],
+    ) -> Result<(FpgaIO, Vec<CellIO>), EvalError> {
+        if let Some(&(row, col)) = probes
+            .iter()
+            .find(|(row, col)| *row >= self.height || *col >= self.width)
+        {
+            return Err(EvalError::ProbeOutOfBounds { row, col });
         }
 
-        Ok(input)
+        let mut values = vec![CellIO::empty(); probes.len()];
+        let output = self.sweep(input, |row, col, cell_io| {
+            for (value, _) in values
+                .iter_mut()
+                .zip(probes)
+                .filter(|(_, probe)| **probe == (row, col))
+            {
+                *value = cell_io;
+            }
+            ControlFlow::Continue(())
+        })?;
+
+        Ok((output, values))
+    }
+
+    /// Evaluates the FPGA like [FPGA::eval] while also recording the
+    /// output [CellIO] of every cell, like probing all of them with
+    /// [FPGA::eval_with_probes] would, but in a single pass.
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok((FpgaIO, Vec<CellIO>))] with the cell outputs in row-major
+    ///   order, so the `(row, col)` one is at `row * width + col`.
+    /// - [Err(EvalError)] if `input` doesn't match the FPGA.
+    pub fn eval_trace(&self, input: FpgaIO) -> Result<(FpgaIO, Vec<CellIO>), EvalError> {
+        let mut trace = vec![CellIO::empty(); self.width * self.height];
+        let output = self.sweep(input, |row, col, cell_io| {
+            trace[row * self.width + col] = cell_io;
+            ControlFlow::Continue(())
+        })?;
+
+        Ok((output, trace))
+    }
+
+    /// Evaluates the FPGA like [FPGA::eval] with an `input` of any
+    /// length: a shorter one is padded at its end, the columns of
+    /// the last cells, with `false` bits and a longer one has its
+    /// last bits dropped.
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok(FpgaIO)] with the output of the FPGA.
+    /// - [Err(EvalError)] if the evaluation fails or, when `strict`,
+    ///   if any of the dropped bits is set.
+    pub fn eval_padded(&self, input: &[bool], strict: bool) -> Result<FpgaIO, EvalError> {
+        let length = self.width * 2;
+        let loses_bits = input
+            .get(length..)
+            .is_some_and(|dropped| dropped.contains(&true));
+        if loses_bits && strict {
+            return Err(EvalError::SizeMismatch);
+        }
+        #[cfg(feature = "tracing")]
+        if loses_bits {
+            tracing::warn!(
+                length,
+                input = input.len(),
+                "Input truncated, losing set bits"
+            );
+        }
+
+        let mut bits = input[..input.len().min(length)].to_vec();
+        bits.resize(length, false);
+        self.eval(FpgaIO::from(bits.into_boxed_slice()))
+    }
+
+    /// Evaluates an input longer than the FPGA by splitting it in
+    /// tiles of `width * 2` column bits, each one evaluated like
+    /// [FPGA::eval], and concatenating their outputs.
+    ///
+    /// No state is carried between tiles: each one starts with
+    /// cleared row bits, as a separate evaluation would. The last
+    /// tile is padded with `false` bits if it's shorter than the
+    /// FPGA and its output is truncated back to the input length.
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok(Vec<bool>)] with as many bits as `input`.
+    /// - [Err(EvalError)] if the FPGA has no cells to evaluate
+    ///   a non-empty `input` with.
+    pub fn eval_tiled(&self, input: &[bool]) -> Result<Vec<bool>, EvalError> {
+        let tile = self.width * 2;
+        if tile == 0 {
+            return match input.is_empty() {
+                true => Ok(Vec::new()),
+                false => Err(EvalError::SizeMismatch),
+            };
+        }
+
+        let mut output = Vec::with_capacity(input.len());
+        for chunk in input.chunks(tile) {
+            let mut bits = chunk.to_vec();
+            bits.resize(tile, false);
+
+            let tile_output = self.eval(FpgaIO::from(bits.into_boxed_slice()))?;
+            output.extend_from_slice(&tile_output.get_value_vec()[..chunk.len()]);
+        }
+
+        Ok(output)
+    }
+
+    /// Evaluates the FPGA `steps` times, feeding the output of
+    /// each step back as the input of the next one.
+    ///
+    /// The feedback only carries the column bits: the outputs of
+    /// the last row become the column inputs of the first row,
+    /// while the row bits are cleared at the end of every sweep,
+    /// so `initial` is the only step whose row bits are used.
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok(Vec<FpgaIO>)] with the output of every step, stopping
+    ///   early at a fixed point: when a step returns its own input
+    ///   it is the last element, so a sequence shorter than `steps`
+    ///   always ends on a fixed point.
+    /// - [Err(EvalError)] if `initial` doesn't match the FPGA.
+    pub fn eval_sequential(&self, initial: FpgaIO, steps: usize) -> Result<Vec<FpgaIO>, EvalError> {
+        let mut outputs: Vec<FpgaIO> = Vec::with_capacity(steps);
+        let mut input = initial;
+
+        for _ in 0..steps {
+            let output = self.eval(input.clone())?;
+            let settled = output == input;
+
+            outputs.push(output.clone());
+            if settled {
+                break;
+            }
+            input = output;
+        }
+
+        Ok(outputs)
+    }
+
+    /// Evaluates the FPGA sequentially like [FPGA::eval_sequential]
+    /// for at most `max_steps` steps, checking whether it settles.
+    ///
+    /// The column bits of every output are remembered, so a state
+    /// seen before means the board entered a cycle and will never
+    /// settle.
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok(Stability)] with how the board behaved.
+    /// - [Err(EvalError)] if `initial` doesn't match the FPGA.
+    pub fn eval_until_stable(
+        &self,
+        initial: FpgaIO,
+        max_steps: usize,
+    ) -> Result<Stability, EvalError> {
+        let mut seen: BTreeMap<Box<[bool]>, usize> = BTreeMap::new();
+        let mut input = initial;
+
+        for step in 0..max_steps {
+            let output = self.eval(input.clone())?;
+            if output == input {
+                return Ok(Stability::Settled(output));
+            }
+
+            if let Some(first) = seen.insert(output.get_value_vec(), step) {
+                return Ok(Stability::Oscillates {
+                    period: step - first,
+                });
+            }
+            input = output;
+        }
+
+        Ok(Stability::DidNotSettle)
+    }
+
+    /// Runs the boustrophedon sweep over all the cells, calling
+    /// `on_cell` with the coordinates and output of each cell,
+    /// which can stop the sweep by returning [ControlFlow::Break].
+    #[inline]
+    fn sweep(
+        &self,
+        input: FpgaIO,
+        on_cell: impl FnMut(usize, usize, CellIO) -> ControlFlow<()>,
+    ) -> Result<FpgaIO, EvalError> {
+        sweep(
+            self.width,
+            self.height,
+            input,
+            ScanPattern::Boustrophedon,
+            |row, col, cell_io| self.data[row * self.width + col].eval_cell(cell_io),
+            on_cell,
+        )
+    }
+
+    /// Precomputes the truth table of every cell, so that
+    /// repeated evaluations become simple lookups.
+    pub fn compile(&self) -> CompiledFpga {
+        CompiledFpga::new(self)
+    }
+
+    /// Checks the structural invariants of the FPGA: the amount
+    /// of cells must match its dimensions and every cell must
+    /// respect the [Cell] invariants.
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok(())] if the FPGA is well-formed.
+    /// - [Err(Vec<String>)] with every problem found otherwise.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+
+        match self.width.checked_mul(self.height) {
+            Some(cells) if cells == self.data.len() => {}
+            Some(cells) => problems.push(format!(
+                "Expected {cells} cells for a {}x{} FPGA, found {}",
+                self.width,
+                self.height,
+                self.data.len()
+            )),
+            None => problems.push(format!(
+                "The cell count of a {}x{} FPGA overflows",
+                self.width, self.height
+            )),
+        }
+
+        for (i, cell) in self.data.iter().enumerate() {
+            for problem in cell.check() {
+                let (row, col) = match self.width {
+                    0 => (0, i),
+                    width => (i / width, i % width),
+                };
+                problems.push(format!("Cell ({row}, {col}): {problem}"));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
     }
 
     #[inline]
@@ -81,9 +648,169 @@ impl FPGA {
     pub fn width(&self) -> usize {
         self.width
     }
+
+    /// Returns the `(width, height)` of the FPGA.
+    #[inline]
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Whether the FPGA has no cells because it has no
+    /// columns or no rows.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Encodes the FPGA as [postcard] bytes, which can be
+    /// stored anywhere and decoded back with [FPGA::try_from].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("An FPGA can always be encoded")
+    }
+}
+
+impl From<FPGA> for Vec<u8> {
+    fn from(fpga: FPGA) -> Self {
+        fpga.to_bytes()
+    }
+}
+
+impl TryFrom<&[u8]> for FPGA {
+    type Error = &'static str;
+
+    /// Decodes the bytes made by [FPGA::to_bytes], rejecting
+    /// the ones whose cells don't match the dimensions.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let fpga: FPGA = postcard::from_bytes(bytes).map_err(|_| "Malformed FPGA data")?;
+
+        FPGA::from_cells(fpga.width, fpga.height, fpga.data)
+    }
+}
+
+/// How an [FPGA] behaves when evaluated sequentially,
+/// see [FPGA::eval_until_stable].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stability {
+    /// The output stopped changing, returning itself as input.
+    Settled(FpgaIO),
+    /// The outputs repeat every `period` steps.
+    Oscillates { period: usize },
+    /// Neither a fixed point nor a cycle was reached in time.
+    DidNotSettle,
+}
+
+/// The order the cells of each row are evaluated in, the
+/// rows are always evaluated from the first to the last one.
+/// The row signals are carried from a cell to the next one
+/// of the same row and cleared when moving to the next row.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScanPattern {
+    /// From left to right on even rows and from right to left
+    /// on odd ones, the order used by [FPGA::eval].
+    #[default]
+    Boustrophedon,
+    /// From left to right on every row.
+    LeftToRight,
+}
+
+/// The reasons why an [FPGA] evaluation can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvalError {
+    /// The [FpgaIO] size does not match the FPGA width.
+    SizeMismatch,
+    /// A probed cell is outside the FPGA.
+    ProbeOutOfBounds { row: usize, col: usize },
+    /// The evaluation was stopped before completing.
+    Cancelled,
+    /// The FPGA has no rows or no columns to evaluate.
+    EmptyGrid,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::SizeMismatch => {
+                write!(f, "FpgaIO size does not match grid input requirements")
+            }
+            EvalError::ProbeOutOfBounds { row, col } => {
+                write!(f, "Probe ({row}, {col}) is outside the FPGA")
+            }
+            EvalError::Cancelled => write!(f, "Evaluation cancelled"),
+            EvalError::EmptyGrid => write!(f, "The FPGA has no cells to evaluate"),
+        }
+    }
+}
+
+impl core::error::Error for EvalError {}
+
+/// Runs the sweep over a `width` x `height` grid in the order given
+/// by `pattern`, using `eval_cell` to evaluate the cell at the given
+/// `(row, col)` and calling `on_cell` with the coordinates and output
+/// of each cell, which can stop the sweep by returning
+/// [ControlFlow::Break].
+///
+/// On a single column both ends of a row are the same cell, so the
+/// sweep just walks up the rows and the row outputs of each cell
+/// are dropped when moving to the next one.
+#[inline]
+pub(crate) fn sweep(
+    width: usize,
+    height: usize,
+    mut input: FpgaIO,
+    pattern: ScanPattern,
+    eval_cell: impl Fn(usize, usize, CellIO) -> CellIO,
+    mut on_cell: impl FnMut(usize, usize, CellIO) -> ControlFlow<()>,
+) -> Result<FpgaIO, EvalError> {
+    if width == 0 || height == 0 {
+        return Err(EvalError::EmptyGrid);
+    }
+    if input.bit_len() != width * 2 {
+        return Err(EvalError::SizeMismatch);
+    }
+
+    #[cfg(feature = "tracing")]
+    let _span = tracing::trace_span!("sweep", width, height).entered();
+
+    for j in 0..height {
+        let reversed = pattern == ScanPattern::Boustrophedon && j % 2 == 1;
+
+        for k in 0..width {
+            let i = if reversed { width - 1 - k } else { k };
+            let cell_io = eval_cell(j, i, input.cell_io_at(i));
+
+            input.set(i, cell_io);
+            if on_cell(j, i, cell_io).is_break() {
+                return Err(EvalError::Cancelled);
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(row = j, reversed, "Row completed");
+
+        input.reset_row_io();
+    }
+
+    Ok(input)
 }
 
-#[derive(Debug, Clone)]
+/// The lines going through an [FPGA] while it's swept: the 2
+/// column bits of every cell of a row and the 2 row bits carried
+/// from one cell to the next.
+///
+/// The column bits are packed from the lowest bit of the first
+/// byte, the cell at position `i` owning bits `2 * i` (Column 1)
+/// and `2 * i + 1` (Column 2). The row bits are shared by the
+/// whole row and kept in the 2 highest bits of the last byte,
+/// Row 1 then Row 2, which is why there's always room for them:
+///
+/// ```text
+/// 3 cells:  [R2 R1 c2 c2 c1 c1 c0 c0]
+/// 4 cells:  [c3 c3 c2 c2 c1 c1 c0 c0] [R2 R1 0 0 0 0 0 0]
+/// ```
+///
+/// `trim` is the amount of column bits in the byte holding the
+/// row bits, 0 when they have a byte of their own.
+#[derive(Clone)]
 pub struct FpgaIO {
     io: Box<[u8]>,
     trim: u8,
@@ -93,15 +820,10 @@ impl FpgaIO {
     #[inline]
     pub fn new(mut length: usize) -> Self {
         length += 2;
-        let pagination = length / 8 + (length % 8 > 0) as usize;
-        let mut io = Vec::with_capacity(pagination);
-
-        for _ in 0..pagination {
-            io.push(0);
-        }
+        let pagination = length / 8 + !length.is_multiple_of(8) as usize;
 
         Self {
-            io: io.into_boxed_slice(),
+            io: vec![0; pagination].into_boxed_slice(),
             trim: ((length - 2) % 8) as u8,
         }
     }
@@ -111,10 +833,29 @@ impl FpgaIO {
         self.io.len()
     }
 
+    /// The number of column bits held by this [FpgaIO].
+    #[inline]
+    fn bit_len(&self) -> usize {
+        // The last byte always keeps its 2 highest bits for the rows,
+        // so only a `trim` of 7 pushes them into a byte of their own.
+        let full_bytes = self.len() - 1 - (self.trim == 7) as usize;
+        full_bytes * 8 + self.trim as usize
+    }
+
+    /// The 2 row bits, kept in the highest bits of the last byte.
+    #[inline]
+    fn row_bits(&self) -> u8 {
+        self.io[self.len() - 1] >> 6
+    }
+
+    /// The input of the cell at `cell_pos`: its own 2 column
+    /// bits, along with the row bits left by the previous cell
+    /// of the row.
     #[inline]
     fn cell_io_at(&self, cell_pos: usize) -> CellIO {
-        let pagination = cell_pos / 8;
-        let trim = cell_pos % 8;
+        // Every cell owns 2 column bits.
+        let pagination = cell_pos * 2 / 8;
+        let trim = cell_pos * 2 % 8;
 
         let mut bits: u8 = (self.io[pagination] >> trim) & 0b11;
         bits |= (self.io[self.len() - 1] >> 4) & 0b1100;
@@ -122,33 +863,140 @@ impl FpgaIO {
         CellIO::from_bits_truncate(bits)
     }
 
+    /// Writes the output of the cell at `cell_pos`, the inverse
+    /// of [FpgaIO::cell_io_at]: its column bits replace the
+    /// ones of the cell and its row bits the shared ones, read
+    /// by the next cell of the row.
     #[inline]
     pub fn set(&mut self, cell_pos: usize, value: CellIO) {
-        let pagination = cell_pos / 8;
-        let trim = cell_pos % 8;
+        let pagination = cell_pos * 2 / 8;
+        let trim = cell_pos * 2 % 8;
 
-        let mut bits: u8 = value.bits();
+        let bits: u8 = value.bits();
         self.io[pagination] &= !(0b11 << trim);
         self.io[pagination] |= (bits & 0b11) << trim;
-        bits = bits << 4;
-        self.io[self.len() - 1] &= !(0b11 << 6);
-        self.io[self.len() - 1] |= (bits & (0b11 << 2)) << 6;
+        // Row 1 and Row 2 are bits 2 and 3 of a CellIO,
+        // and bits 6 and 7 of the last byte.
+        let last = self.len() - 1;
+        self.io[last] &= !(0b11 << 6);
+        self.io[last] |= (bits & 0b1100) << 4;
     }
 
+    /// Clears the row bits, so that a row doesn't carry the
+    /// row lines of the previous one. The column bits sharing
+    /// their byte are left untouched.
     #[inline]
     fn reset_row_io(&mut self) {
         self.io[self.len() - 1] &= !(0b11 << 6);
     }
 
+    /// Iterates over the column bits in order, without
+    /// allocating like [FpgaIO::get_value_vec] does.
+    #[inline]
+    pub fn iter_bits(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.bit_len()).map(|i| (self.io[i / 8] >> (i % 8)) & 1 == 1)
+    }
+
+    /// Returns the column bits, the inverse of the
+    /// `From<Box<[bool]>>` conversion.
     #[inline]
     pub fn get_value_vec(&self) -> Box<[bool]> {
-        let mut io_vec = vec![false; self.io.len() - 1 + self.trim as usize].into_boxed_slice();
-        for byte in self.io.as_ref() {
-            for bit in 0..8 {
-                io_vec[(byte * 8 + bit) as usize] = (byte & (1 << bit)) != 0;
-            }
+        self.iter_bits().collect()
+    }
+
+    /// Creates an [FpgaIO] of `length` column bits from the bits of
+    /// `value`: its least significant bit is the first column bit,
+    /// column 1 of the cell at position 0, the next one is its
+    /// column 2 and so on. Bits past `length` are ignored and
+    /// column bits past the 64th are left cleared.
+    #[inline]
+    pub fn from_u64(length: usize, value: u64) -> Self {
+        let bits: Box<[bool]> = (0..length)
+            .map(|i| i < 64 && (value >> i) & 1 == 1)
+            .collect();
+        Self::from(bits)
+    }
+
+    /// Returns the column bits as an integer, laid out like
+    /// [FpgaIO::from_u64] expects them.
+    ///
+    /// ## Returns
+    ///
+    /// - [Some(u64)] with the column bits.
+    /// - [None] if there are more than 64 column bits.
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        if self.bit_len() > 64 {
+            return None;
         }
-        io_vec
+        Some(
+            self.iter_bits()
+                .enumerate()
+                .fold(0, |value, (i, bit)| value | (bit as u64) << i),
+        )
+    }
+}
+
+impl Default for FpgaIO {
+    /// An [FpgaIO] without column bits, like `FpgaIO::new(0)`,
+    /// which still holds the row bits.
+    #[inline]
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl fmt::Display for FpgaIO {
+    /// Writes the column bits as a string of `0` and `1`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for bit in self.iter_bits() {
+            f.write_char(if bit { '1' } else { '0' })?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for FpgaIO {
+    /// Two [FpgaIO]s are equal when they hold the same column
+    /// and row bits, regardless of their unused bits.
+    fn eq(&self, other: &Self) -> bool {
+        self.bit_len() == other.bit_len()
+            && self.row_bits() == other.row_bits()
+            && self.iter_bits().eq(other.iter_bits())
+    }
+}
+
+impl Eq for FpgaIO {}
+
+impl fmt::Debug for FpgaIO {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes: Vec<String> = self.io.iter().map(|byte| format!("{byte:08b}")).collect();
+        let rows = self.row_bits();
+
+        f.debug_struct("FpgaIO")
+            .field("columns", &format_args!("{self}"))
+            .field("rows", &format_args!("{}{}", rows & 1, rows >> 1))
+            .field("bytes", &format_args!("[{}]", bytes.join(", ")))
+            .field("trim", &self.trim)
+            .finish()
+    }
+}
+
+impl FromStr for FpgaIO {
+    type Err = &'static str;
+
+    /// Parses the column bits from a string of `0` and `1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bits = s
+            .chars()
+            .map(|c| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err("Invalid bit, expected 0 or 1"),
+            })
+            .collect::<Result<Box<[bool]>, _>>()?;
+
+        Ok(Self::from(bits))
     }
 }
 
@@ -156,7 +1004,7 @@ impl From<Box<[bool]>> for FpgaIO {
     #[inline]
     fn from(value: Box<[bool]>) -> Self {
         let capacity = value.len() + 2;
-        let pagination = capacity / 8 + (capacity % 8 > 0) as usize;
+        let pagination = capacity / 8 + !capacity.is_multiple_of(8) as usize;
         let mut flags = vec![0u8; pagination];
 
         for (i, val) in value.iter().enumerate() {
@@ -170,9 +1018,203 @@ impl From<Box<[bool]>> for FpgaIO {
     }
 }
 
+/// The position of the `line` column bit of the cell at
+/// `col`, in the order of [FpgaIO::iter_bits].
+#[inline]
+fn column_bit(col: usize, line: Selector) -> Result<usize, &'static str> {
+    match line {
+        Selector::Column1 | Selector::Column2 => Ok(col * 2 + line as usize),
+        Selector::Row1 | Selector::Row2 => Err("Only the column lines are inputs"),
+    }
+}
+
+impl FpgaIO {
+    /// Reads the `line` input of the cell at `col`, the inverse
+    /// of [FpgaIoBuilder::set].
+    ///
+    /// ## Returns
+    ///
+    /// - [None] if `line` isn't a column or `col` is out of bounds.
+    #[inline]
+    pub fn line(&self, col: usize, line: Selector) -> Option<bool> {
+        let bit = column_bit(col, line).ok()?;
+        (bit < self.bit_len()).then(|| (self.io[bit / 8] >> (bit % 8)) & 1 == 1)
+    }
+}
+
+/// Builds the [FpgaIO] of a board `columns` cells wide by
+/// setting its inputs by cell, so that the position of each
+/// bit never has to be worked out by hand.
+///
+/// ```
+/// use simulator_core::FpgaIoBuilder;
+/// use simulator_core::cell::Selector;
+///
+/// let io = FpgaIoBuilder::new(2)
+///     .set(1, Selector::Column2, true)?
+///     .build();
+/// assert_eq!(io.to_string(), "0001");
+/// # Ok::<(), &'static str>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FpgaIoBuilder {
+    bits: Vec<bool>,
+}
+
+impl FpgaIoBuilder {
+    /// Starts with every input of the `columns` cells cleared.
+    #[inline]
+    pub fn new(columns: usize) -> Self {
+        Self {
+            bits: vec![false; columns * 2],
+        }
+    }
+
+    /// Sets the `line` input of the cell at `col` to `value`.
+    ///
+    /// ## Returns
+    ///
+    /// - [Err()] if `line` isn't a column, since the row lines
+    ///   aren't inputs, or `col` is out of bounds.
+    #[inline]
+    pub fn set(
+        &mut self,
+        col: usize,
+        line: Selector,
+        value: bool,
+    ) -> Result<&mut Self, &'static str> {
+        let bit = column_bit(col, line)?;
+        *self.bits.get_mut(bit).ok_or("Column out of bounds")? = value;
+        Ok(self)
+    }
+
+    /// Packs the inputs set so far into an [FpgaIO].
+    #[inline]
+    pub fn build(&self) -> FpgaIO {
+        FpgaIO::from(self.bits.clone().into_boxed_slice())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::FpgaIO;
+    use crate::cell::{ActivationOrder, Cell, CellFlags, Fills, Selector};
+    use crate::{
+        CellIO, CompiledFpga, EvalError, FPGA, FpgaIO, FpgaIoBuilder, ScanPattern, Stability, sweep,
+    };
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+    use alloc::{format, vec};
+    use core::ops::ControlFlow;
+
+    #[test]
+    fn fpga_io_builder() {
+        let io = FpgaIoBuilder::new(3)
+            .set(0, Selector::Column2, true)
+            .unwrap()
+            .set(2, Selector::Column1, true)
+            .unwrap()
+            .build();
+        assert_eq!(*io.io, [0b0001_0010]);
+        assert_eq!(io.trim, 6);
+
+        let mut builder = FpgaIoBuilder::new(5);
+        builder.set(3, Selector::Column2, true).unwrap();
+        builder.set(4, Selector::Column1, true).unwrap();
+        builder.set(4, Selector::Column2, true).unwrap();
+        builder.set(4, Selector::Column2, false).unwrap();
+        let io = builder.build();
+        assert_eq!(*io.io, [0b1000_0000, 0b0000_0001]);
+        assert_eq!(io, FpgaIO::from_u64(10, 0b01_1000_0000));
+
+        for col in 0..5 {
+            for line in [Selector::Column1, Selector::Column2] {
+                let expected =
+                    matches!((col, line), (3, Selector::Column2) | (4, Selector::Column1));
+                assert_eq!(io.line(col, line), Some(expected));
+            }
+            assert_eq!(io.line(col, Selector::Row1), None);
+        }
+        assert_eq!(io.line(5, Selector::Column1), None);
+
+        assert!(builder.set(5, Selector::Column1, true).is_err());
+        assert!(builder.set(0, Selector::Row2, true).is_err());
+    }
+
+    #[test]
+    fn row_io_bits() {
+        // The row bits share the last byte with 3 cells, and have
+        // one of their own with 4.
+        for columns in [3, 4] {
+            let mut io = FpgaIO::from_u64(columns * 2, 0b10_01);
+            let last = io.io.len() - 1;
+            io.io[last] |= 0b01 << 6;
+            assert_eq!(io.row_bits(), 0b01);
+
+            assert_eq!(io.cell_io_at(0), CellIO::COLUMN_1 | CellIO::ROW_1);
+            assert_eq!(io.cell_io_at(1), CellIO::COLUMN_2 | CellIO::ROW_1);
+            for pos in 2..columns {
+                assert_eq!(io.cell_io_at(pos), CellIO::ROW_1);
+            }
+
+            io.io[last] |= 0b10 << 6;
+            assert_eq!(io.cell_io_at(columns - 1), CellIO::ROW_1 | CellIO::ROW_2);
+
+            let columns_before = io.get_value_vec();
+            io.reset_row_io();
+            assert_eq!(io.row_bits(), 0);
+            assert_eq!(io.get_value_vec(), columns_before);
+            assert_eq!(io.cell_io_at(0), CellIO::COLUMN_1);
+        }
+
+        // The column bits of the shared byte aren't row bits.
+        let io = FpgaIO::from_u64(6, 0b11_0000);
+        assert_eq!(io.row_bits(), 0);
+        assert_eq!(io.cell_io_at(2), CellIO::COLUMN_1 | CellIO::COLUMN_2);
+    }
+
+    #[test]
+    fn set_round_trip() {
+        for columns in 1..=9 {
+            for pos in 0..columns {
+                for value in CellIO::combinations() {
+                    let mut io = FpgaIO::from_u64(columns * 2, u64::MAX);
+                    io.set(pos, value);
+                    assert_eq!(io.cell_io_at(pos), value, "{columns} cells, at {pos}");
+
+                    // The other cells keep their columns and see the new rows.
+                    let rows = value & (CellIO::ROW_1 | CellIO::ROW_2);
+                    for other in (0..columns).filter(|&other| other != pos) {
+                        assert_eq!(
+                            io.cell_io_at(other),
+                            CellIO::COLUMN_1 | CellIO::COLUMN_2 | rows
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rows_carry_to_the_next_cell() {
+        // The first cell drives Row 1, which the second one copies
+        // to its Column 1.
+        let output = sweep(
+            2,
+            1,
+            FpgaIO::new(4),
+            ScanPattern::Boustrophedon,
+            |_, col, cell_io| match col {
+                0 => CellIO::ROW_1,
+                _ if cell_io.contains(CellIO::ROW_1) => CellIO::COLUMN_1,
+                _ => CellIO::empty(),
+            },
+            |_, _, _| ControlFlow::Continue(()),
+        )
+        .unwrap();
+        assert_eq!(output.to_string(), "0010");
+        assert_eq!(output.row_bits(), 0);
+    }
 
     #[test]
     fn new_fpga_io() {
@@ -187,5 +1229,965 @@ mod tests {
         let fpga_io = FpgaIO::new(20);
         assert_eq!(fpga_io.io.len(), 3);
         assert_eq!(fpga_io.trim, 4);
+
+        let fpga_io = FpgaIO::default();
+        assert_eq!(fpga_io, FpgaIO::new(0));
+        assert_eq!(fpga_io.io.len(), 1);
+        assert_eq!(fpga_io.bit_len(), 0);
+        assert_eq!(fpga_io.to_string(), "");
+    }
+
+    #[test]
+    fn fpga_io_bit_len() {
+        for length in 0..40 {
+            assert_eq!(FpgaIO::new(length).bit_len(), length);
+        }
+    }
+
+    #[test]
+    fn fpga_io_string_round_trip() {
+        let bits = "1011001110";
+        let fpga_io: FpgaIO = bits.parse().unwrap();
+        assert_eq!(fpga_io.bit_len(), bits.len());
+        assert_eq!(fpga_io.to_string(), bits);
+
+        assert!("10x1".parse::<FpgaIO>().is_err());
+    }
+
+    #[test]
+    fn fpga_io_iter_bits() {
+        let mut rng = Rng(0x94D0_49BB_1331_11EB);
+
+        // Every trim, with the row bits set so that they
+        // would show up if they were read as columns.
+        for len in 0..=17 {
+            let bits: Box<[bool]> = (0..len).map(|_| rng.next() & 1 == 1).collect();
+            let mut fpga_io = FpgaIO::from(bits.clone());
+            let last = fpga_io.len() - 1;
+            fpga_io.io[last] |= 0b11 << 6;
+
+            assert!(fpga_io.iter_bits().eq(bits.iter().copied()));
+            assert_eq!(
+                fpga_io.iter_bits().collect::<Box<[bool]>>(),
+                fpga_io.get_value_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn fpga_io_u64_round_trip() {
+        let fpga_io = FpgaIO::from_u64(6, 0b100110);
+        assert_eq!(fpga_io.to_string(), "011001");
+        assert_eq!(fpga_io.as_u64(), Some(0b100110));
+        // The bits that don't fit are dropped.
+        assert_eq!(FpgaIO::from_u64(3, 0b1111).as_u64(), Some(0b111));
+        assert_eq!(FpgaIO::from_u64(0, u64::MAX).as_u64(), Some(0));
+
+        let values = [0, 1, 1 << 62, 1 << 63, u64::MAX, 0xDEAD_BEEF_0BAD_F00D];
+        for length in 60..=64 {
+            let mask = u64::MAX >> (64 - length);
+            for value in values {
+                let fpga_io = FpgaIO::from_u64(length, value);
+                assert_eq!(fpga_io.bit_len(), length);
+                assert_eq!(fpga_io.as_u64(), Some(value & mask));
+            }
+        }
+
+        let fpga_io = FpgaIO::from_u64(65, u64::MAX);
+        assert_eq!(fpga_io.as_u64(), None);
+        assert_eq!(fpga_io.iter_bits().filter(|bit| *bit).count(), 64);
+        assert!(!fpga_io.iter_bits().last().unwrap());
+    }
+
+    #[test]
+    fn fpga_io_equality() {
+        let a: FpgaIO = "101100".parse().unwrap();
+        let mut b = FpgaIO::new(6);
+        b.set(0, CellIO::new(true, false, false, false));
+        b.set(1, CellIO::new(true, true, false, false));
+        b.reset_row_io();
+
+        assert_eq!(a, b);
+        assert_ne!(a, "101101".parse().unwrap());
+        assert_ne!(a, "1011000".parse().unwrap());
+
+        // Unused bits don't take part in the comparison.
+        let a: FpgaIO = "1011001110".parse().unwrap();
+        let mut c = a.clone();
+        c.io[1] |= 0b0011_1100;
+        assert_eq!(a, c);
+    }
+
+    #[test]
+    fn fpga_io_debug() {
+        let io: FpgaIO = "1011001110".parse().unwrap();
+        assert_eq!(
+            format!("{io:?}"),
+            "FpgaIO { columns: 1011001110, rows: 00, bytes: [11001101, 00000001], trim: 2 }"
+        );
+    }
+
+    #[test]
+    fn eval_accepts_matching_input() {
+        let fpga = FPGA::new(3, 2);
+
+        assert!(fpga.eval(FpgaIO::new(6)).is_ok());
+        assert_eq!(
+            fpga.eval(FpgaIO::new(5)).unwrap_err(),
+            EvalError::SizeMismatch
+        );
+        assert_eq!(
+            fpga.eval(FpgaIO::new(7)).unwrap_err(),
+            EvalError::SizeMismatch
+        );
+    }
+
+    #[test]
+    fn eval_padded() {
+        let mut rng = Rng(0xE703_7ED1_A0B4_28DB);
+        let fpga = FPGA::from_cells(3, 2, (0..6).map(|_| rng.cell()).collect()).unwrap();
+        let eval = |bits: &str| fpga.eval(bits.parse().unwrap()).unwrap();
+        let bits = |bits: &str| -> Vec<bool> { bits.chars().map(|c| c == '1').collect() };
+
+        for strict in [false, true] {
+            assert_eq!(fpga.eval_padded(&bits("1101"), strict), Ok(eval("110100")));
+            assert_eq!(
+                fpga.eval_padded(&bits("011010"), strict),
+                Ok(eval("011010"))
+            );
+            assert_eq!(
+                fpga.eval_padded(&bits("01101000"), strict),
+                Ok(eval("011010"))
+            );
+            assert_eq!(fpga.eval_padded(&[], strict), Ok(eval("000000")));
+        }
+
+        assert_eq!(
+            fpga.eval_padded(&bits("0110100001"), false),
+            Ok(eval("011010"))
+        );
+        assert_eq!(
+            fpga.eval_padded(&bits("0110100001"), true),
+            Err(EvalError::SizeMismatch)
+        );
+        assert_eq!(
+            FPGA::new(0, 2).eval_padded(&[], false),
+            Err(EvalError::EmptyGrid)
+        );
+    }
+
+    #[test]
+    fn eval_with_pattern() {
+        let mut rng = Rng(0x8CB9_2BA7_2F3D_8DD7);
+        for _ in 0..20 {
+            let fpga = rng.fpga();
+            let input = FpgaIO::new(fpga.width() * 2);
+            assert_eq!(
+                fpga.eval_with_pattern(input.clone(), ScanPattern::default()),
+                fpga.eval(input)
+            );
+        }
+
+        let order = |pattern| {
+            let mut visited = Vec::new();
+            sweep(
+                3,
+                3,
+                FpgaIO::new(6),
+                pattern,
+                |_, _, cell_io| cell_io,
+                |row, col, _| {
+                    visited.push((row, col));
+                    ControlFlow::Continue(())
+                },
+            )
+            .unwrap();
+            visited
+        };
+        assert_eq!(
+            order(ScanPattern::Boustrophedon),
+            [
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 2),
+                (1, 1),
+                (1, 0),
+                (2, 0),
+                (2, 1),
+                (2, 2)
+            ]
+        );
+        assert_eq!(
+            order(ScanPattern::LeftToRight),
+            [
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 1),
+                (1, 2),
+                (2, 0),
+                (2, 1),
+                (2, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn eval_empty_grid() {
+        for (width, height) in [(0, 0), (0, 3), (3, 0)] {
+            let fpga = FPGA::new(width, height);
+            let input = FpgaIO::new(width * 2);
+
+            assert_eq!(fpga.eval(input.clone()), Err(EvalError::EmptyGrid));
+            assert_eq!(
+                fpga.eval_trace(input.clone()).unwrap_err(),
+                EvalError::EmptyGrid
+            );
+            assert_eq!(
+                fpga.eval_sequential(input.clone(), 4),
+                Err(EvalError::EmptyGrid)
+            );
+            assert_eq!(fpga.compile().eval(input), Err(EvalError::EmptyGrid));
+            assert_eq!(fpga.eval_tiled(&[]), Ok(Vec::new()));
+        }
+    }
+
+    #[test]
+    fn eval_single_column() {
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+
+        for height in 1..8 {
+            let cells: Vec<_> = (0..height).map(|_| rng.cell()).collect();
+            let fpga = FPGA::from_cells(1, height, cells.clone()).unwrap();
+
+            for input in 0..4 {
+                // Each cell gets the columns of the one below
+                // it and no row signals.
+                let expected = cells.iter().fold(input, |columns, cell| {
+                    cell.eval_cell(CellIO::from_bits_truncate(columns)).bits() & 0b11
+                });
+
+                let output = fpga.eval(FpgaIO::from_u64(2, input as u64)).unwrap();
+                assert_eq!(output.as_u64(), Some(expected as u64));
+            }
+        }
+    }
+
+    #[test]
+    fn row_and_column() {
+        let mut fpga = FPGA::new(3, 2);
+        for row in 0..2 {
+            for col in 0..3 {
+                fpga.get_mut(row, col).unwrap().fills = Fills::new(row as u8, col as u8, 0, 0);
+            }
+        }
+
+        let row = fpga.row(1).unwrap();
+        assert_eq!(row.len(), 3);
+        for (col, cell) in row.iter().enumerate() {
+            assert_eq!(Some(cell), fpga.get_cell(1, col));
+        }
+
+        let column = fpga.column(2).unwrap();
+        assert_eq!(column.len(), 2);
+        for (row, cell) in column.into_iter().enumerate() {
+            assert_eq!(Some(cell), fpga.get_cell(row, 2));
+        }
+
+        assert_eq!(fpga.row(2), None);
+        assert_eq!(fpga.column(3), None);
+    }
+
+    #[test]
+    fn fpga_equality() {
+        let mut fpga = FPGA::new(3, 2);
+        assert_eq!(fpga, FPGA::new(3, 2));
+        // The same cells, laid out differently.
+        assert_ne!(fpga, FPGA::new(2, 3));
+
+        fpga.get_mut(1, 2).unwrap().flags |= CellFlags::C1_OUT;
+        assert_ne!(fpga, FPGA::new(3, 2));
+        assert_eq!(FPGA::try_from(fpga.to_bytes().as_slice()), Ok(fpga.clone()));
+        assert_eq!(fpga.clone(), fpga);
+    }
+
+    #[test]
+    fn copy_cell() {
+        let mut fpga = FPGA::new(3, 2);
+        fpga.get_mut(0, 1).unwrap().fills = Fills::new(1, 2, 3, 0);
+
+        assert_eq!(fpga.copy_cell((0, 1), (1, 2)), Ok(()));
+        assert_eq!(fpga.get_cell(1, 2), fpga.get_cell(0, 1));
+
+        let before = fpga.clone();
+        assert!(fpga.copy_cell((0, 1), (2, 0)).is_err());
+        assert!(fpga.copy_cell((0, 3), (0, 0)).is_err());
+        assert!(fpga.cells().eq(before.cells()));
+    }
+
+    #[test]
+    fn fill_region() {
+        let mut fpga = FPGA::new(4, 3);
+        let mut cell = Cell::default();
+        cell.flags.set(CellFlags::NOT_C1, true);
+
+        assert_eq!(fpga.fill_region((1, 1), (2, 2), cell), Ok(()));
+        for row in 0..3 {
+            for col in 0..4 {
+                let filled = (1..=2).contains(&row) && (1..=2).contains(&col);
+                assert_eq!(fpga.get_cell(row, col) == Some(&cell), filled);
+            }
+        }
+
+        let before = fpga.clone();
+        assert!(fpga.fill_region((0, 0), (3, 0), Cell::default()).is_err());
+        assert!(fpga.fill_region((0, 2), (0, 1), Cell::default()).is_err());
+        assert!(fpga.cells().eq(before.cells()));
+    }
+
+    #[test]
+    fn apply_to_region() {
+        let mut fpga = FPGA::new(3, 2);
+        let region = [(0, 0), (1, 2)];
+
+        assert_eq!(
+            fpga.apply_to_region(region, |cell| {
+                cell.flags.insert(CellFlags::NOT_C1);
+                cell.flags.remove(CellFlags::SIMULATION);
+            }),
+            Ok(())
+        );
+        for (row, col) in [(0, 0), (0, 1), (1, 2)] {
+            let cell = fpga.get_cell(row, col).unwrap();
+            assert_eq!(
+                cell.flags.contains(CellFlags::NOT_C1),
+                region.contains(&(row, col))
+            );
+            assert!(cell.flags.contains(CellFlags::SIMULATION));
+        }
+
+        let source = Cell {
+            flags: CellFlags::JC1_R1 | CellFlags::R1_OUT,
+            ..Cell::default()
+        };
+        fpga.apply_to_region([(0, 1), (1, 2)], |cell| *cell = source)
+            .unwrap();
+        assert_eq!(
+            fpga.get_cell(1, 2).unwrap().flags,
+            CellFlags::JC1_R1 | CellFlags::R1_OUT | CellFlags::SIMULATION
+        );
+
+        let before = fpga.clone();
+        assert!(
+            fpga.apply_to_region([(0, 0), (2, 0)], |cell| cell.flags = CellFlags::empty())
+                .is_err()
+        );
+        assert_eq!(fpga, before);
+    }
+
+    #[test]
+    fn map_cells() {
+        let mut fpga = FPGA::new(3, 2);
+        fpga.get_mut(1, 2).unwrap().flags.insert(CellFlags::OUTPUTS);
+
+        let mut visited = Vec::new();
+        fpga.map_cells(|row, col, cell| {
+            visited.push((row, col));
+            // Clears all the outputs and negates the second column.
+            cell.flags.remove(CellFlags::OUTPUTS);
+            cell.flags.set(CellFlags::NOT_C1, col == 1);
+        });
+
+        assert_eq!(visited, [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
+        assert_eq!(fpga.dimensions(), (3, 2));
+        assert_eq!(fpga.get_cell(1, 2), Some(&Cell::default()));
+        assert!(
+            fpga.get_cell(0, 1)
+                .unwrap()
+                .flags
+                .contains(CellFlags::NOT_C1)
+        );
+        assert_eq!(fpga.used_cell_count(), 2);
+
+        FPGA::new(0, 3).map_cells(|_, _, _| unreachable!());
+    }
+
+    #[test]
+    fn dimensions() {
+        let fpga = FPGA::new(4, 0);
+        assert_eq!(fpga.dimensions(), (4, 0));
+        assert!(fpga.is_empty());
+        assert_eq!(fpga.cells().count(), 0);
+
+        assert!(FPGA::new(0, 3).is_empty());
+        assert!(FPGA::default().is_empty());
+        assert!(!FPGA::new(1, 1).is_empty());
+        assert_eq!(FPGA::new(3, 2).dimensions(), (3, 2));
+    }
+
+    #[test]
+    fn used_cell_count() {
+        let mut fpga = FPGA::new(3, 3);
+        assert_eq!(fpga.used_cell_count(), 0);
+        assert!(fpga.cells().all(Cell::is_default));
+
+        fpga.get_mut(0, 2).unwrap().fills = Fills::new(0, 1, 0, 0);
+        fpga.get_mut(2, 1)
+            .unwrap()
+            .flags
+            .insert(CellFlags::DISABLED);
+        assert_eq!(fpga.used_cell_count(), 2);
+        assert!(!fpga.get_cell(0, 2).unwrap().is_default());
+    }
+
+    #[test]
+    fn fold_cells() {
+        let mut fpga = FPGA::new(3, 2);
+        fpga.get_mut(0, 0).unwrap().fills = Fills::new(4, 0, 0, 0);
+        fpga.get_mut(1, 2).unwrap().fills = Fills::new(0, 0, 7, 0);
+        fpga.get_mut(1, 2).unwrap().flags.insert(CellFlags::C1_OUT);
+
+        let outputs = fpga.fold_cells(0, |count, cell| {
+            count + cell.flags.contains(CellFlags::C1_OUT) as usize
+        });
+        assert_eq!(outputs, 1);
+
+        // Cells are visited in row-major order.
+        let visited = fpga.fold_cells(Vec::new(), |mut visited, cell| {
+            visited.push(cell.fills);
+            visited
+        });
+        assert_eq!(visited.first(), Some(&Fills::new(4, 0, 0, 0)));
+        assert_eq!(visited.last(), Some(&Fills::new(0, 0, 7, 0)));
+        assert_eq!(FPGA::default().fold_cells(0, |count, _| count + 1), 0);
+    }
+
+    #[test]
+    fn eval_tiled() {
+        let mut rng = Rng(0x5851_F42D_4C95_7F2D);
+        let fpga = FPGA::from_cells(2, 3, (0..6).map(|_| rng.cell()).collect()).unwrap();
+        let eval = |bits: &[bool]| fpga.eval(FpgaIO::from(Box::from(bits))).unwrap();
+
+        // Exactly one, two and two and a half times the board input.
+        for len in [4, 8, 10] {
+            let input: Vec<bool> = (0..len).map(|_| rng.next() & 1 == 1).collect();
+            let output = fpga.eval_tiled(&input).unwrap();
+            assert_eq!(output.len(), len);
+
+            for (bits, out) in input.chunks(4).zip(output.chunks(4)) {
+                let mut bits = bits.to_vec();
+                bits.resize(4, false);
+                assert_eq!(out, &eval(&bits).get_value_vec()[..out.len()]);
+            }
+        }
+
+        assert_eq!(fpga.eval_tiled(&[]), Ok(Vec::new()));
+        assert_eq!(FPGA::new(0, 2).eval_tiled(&[]), Ok(Vec::new()));
+        assert_eq!(
+            FPGA::new(0, 2).eval_tiled(&[true]),
+            Err(EvalError::SizeMismatch)
+        );
+    }
+
+    /// A small xorshift generator, enough to build random boards.
+    pub(crate) struct Rng(pub(crate) u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, max: u64) -> u64 {
+            self.next() % max
+        }
+
+        pub(crate) fn cell(&mut self) -> Cell {
+            let mut order = [
+                Selector::Column1,
+                Selector::Column2,
+                Selector::Row1,
+                Selector::Row2,
+            ];
+            for i in (1..order.len()).rev() {
+                order.swap(i, self.below(i as u64 + 1) as usize);
+            }
+
+            let flags = CellFlags::from_bits_truncate(self.next() as u16);
+            let mut fill = || self.below(Fills::MAX as u64 + 1) as u8;
+            let fills = Fills::new(fill(), fill(), fill(), fill());
+
+            Cell::new(&ActivationOrder::new(order).unwrap(), &flags, fills)
+        }
+
+        pub(crate) fn fpga(&mut self) -> FPGA {
+            let width = self.below(12) as usize;
+            let height = self.below(12) as usize;
+            let data = (0..width * height).map(|_| self.cell()).collect();
+
+            FPGA::from_cells(width, height, data).unwrap()
+        }
+    }
+
+    fn round_trip(fpga: &FPGA) {
+        let bytes = postcard::to_allocvec(fpga).unwrap();
+        assert_eq!(&postcard::from_bytes::<FPGA>(&bytes).unwrap(), fpga);
+        assert_eq!(fpga.to_bytes(), bytes);
+        assert_eq!(FPGA::try_from(bytes.as_slice()).as_ref(), Ok(fpga));
+
+        let json = serde_json::to_string(fpga).unwrap();
+        assert_eq!(&serde_json::from_str::<FPGA>(&json).unwrap(), fpga);
+    }
+
+    #[test]
+    fn bytes_rejected() {
+        let bytes = FPGA::new(3, 2).to_bytes();
+        assert_eq!(
+            FPGA::try_from(&bytes[..bytes.len() - 1]),
+            Err("Malformed FPGA data")
+        );
+
+        let mismatched = FPGA {
+            width: 3,
+            height: 2,
+            data: vec![Cell::default(); 5],
+        };
+        assert_eq!(
+            FPGA::try_from(Vec::from(mismatched).as_slice()),
+            Err("Cell count does not match FPGA dimensions")
+        );
+
+        // Dimensions whose product overflows, which would
+        // otherwise wrap around to the empty data.
+        let overflowing = FPGA {
+            width: 1 << (usize::BITS / 2 + 1),
+            height: 1 << (usize::BITS / 2 - 1),
+            data: Vec::new(),
+        };
+        assert_eq!(
+            FPGA::try_from(Vec::from(overflowing).as_slice()),
+            Err("Cell count does not match FPGA dimensions")
+        );
+    }
+
+    #[test]
+    fn serde_round_trip_random() {
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+
+        for _ in 0..200 {
+            round_trip(&rng.fpga());
+        }
+    }
+
+    #[test]
+    fn serde_round_trip_adversarial() {
+        round_trip(&FPGA::default());
+        round_trip(&FPGA::new(1, 1));
+
+        let order = ActivationOrder::new([
+            Selector::Row2,
+            Selector::Row1,
+            Selector::Column2,
+            Selector::Column1,
+        ])
+        .unwrap();
+        let max = Cell::new(
+            &order,
+            &CellFlags::all(),
+            Fills::new(Fills::MAX, Fills::MAX, Fills::MAX, Fills::MAX),
+        );
+        round_trip(&FPGA::from_cells(3, 2, vec![max; 6]).unwrap());
+
+        // The STILL_* flags are restored on load, so a board
+        // missing them doesn't survive the round trip unchanged.
+        let mut fpga = FPGA::new(2, 2);
+        fpga.get_mut(1, 1)
+            .unwrap()
+            .flags
+            .remove(CellFlags::SIMULATION);
+        let bytes = postcard::to_allocvec(&fpga).unwrap();
+        assert_eq!(
+            postcard::from_bytes::<FPGA>(&bytes).unwrap(),
+            FPGA::new(2, 2)
+        );
+    }
+
+    #[test]
+    fn compiled_eval_matches_eval() {
+        let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+
+        for _ in 0..50 {
+            let fpga = rng.fpga();
+            let compiled = fpga.compile();
+
+            for _ in 0..8 {
+                let bits: Box<[bool]> =
+                    (0..fpga.width() * 2).map(|_| rng.next() & 1 == 1).collect();
+                let input = FpgaIO::from(bits);
+
+                assert_eq!(compiled.eval(input.clone()), fpga.eval(input));
+            }
+        }
+
+        assert_eq!(
+            FPGA::new(2, 2).compile().eval(FpgaIO::new(3)),
+            Err(EvalError::SizeMismatch)
+        );
+    }
+
+    #[test]
+    fn compiled_tables() {
+        let mut fpga = FPGA::new(3, 2);
+        let compiled = fpga.compile();
+
+        let rebuilt = CompiledFpga::from_tables(3, 2, compiled.tables().to_vec()).unwrap();
+        assert_eq!(rebuilt, compiled);
+        assert_eq!(rebuilt.validate(&fpga), Ok(()));
+        assert!(CompiledFpga::from_tables(2, 2, compiled.tables().to_vec()).is_err());
+        assert!(CompiledFpga::from_tables(1 << (usize::BITS - 1), 2, Vec::new()).is_err());
+
+        *fpga.get_mut(1, 2).unwrap() = negate();
+        assert_eq!(
+            compiled.validate(&fpga),
+            Err(vec![
+                "Cell (1, 2): the table doesn't match the cell".to_string()
+            ])
+        );
+        assert!(compiled.validate(&FPGA::new(2, 3)).is_err());
+    }
+
+    #[test]
+    fn validate() {
+        let mut fpga = FPGA::new(3, 2);
+        assert_eq!(fpga.validate(), Ok(()));
+
+        fpga.get_mut(0, 1)
+            .unwrap()
+            .flags
+            .remove(CellFlags::STILL_C2);
+        fpga.get_mut(1, 1).unwrap().fills = Fills::new(0, Fills::MAX + 1, 0, 0);
+        fpga.data.pop();
+
+        let problems = fpga.validate().unwrap_err();
+        assert_eq!(problems.len(), 3);
+        assert!(problems[0].starts_with("Expected 6 cells"));
+        assert!(problems[1].starts_with("Cell (0, 1)"));
+        assert!(problems[2].starts_with("Cell (1, 1)"));
+
+        let overflowing = FPGA {
+            width: usize::MAX,
+            height: 2,
+            data: Vec::new(),
+        };
+        let problems = overflowing.validate().unwrap_err();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].ends_with("FPGA overflows"));
+    }
+
+    #[test]
+    fn normalize() {
+        let mut rng = Rng(0xA076_1D64_78BD_642F);
+        let fpga = rng.fpga();
+
+        let mut broken = fpga.clone();
+        broken.map_cells(|row, col, cell| {
+            if (row + col) % 2 == 0 {
+                cell.flags.remove(CellFlags::SIMULATION);
+            }
+        });
+        if !fpga.is_empty() {
+            assert!(broken.validate().is_err());
+        }
+
+        broken.normalize();
+        assert_eq!(broken, fpga);
+        assert_eq!(broken.validate(), Ok(()));
+    }
+
+    #[test]
+    fn transforms() {
+        let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+        for _ in 0..20 {
+            let mut fpga = rng.fpga();
+            fpga.map_cells(|_, _, cell| cell.flags.remove(CellFlags::NOTS));
+            let (width, height) = fpga.dimensions();
+
+            let mut transformed = fpga.clone();
+            transformed.transpose().unwrap();
+            assert_eq!(transformed.dimensions(), (height, width));
+            transformed.transpose().unwrap();
+            assert_eq!(transformed, fpga);
+
+            for _ in 0..4 {
+                transformed.rotate_90().unwrap();
+            }
+            assert_eq!(transformed, fpga);
+
+            transformed.flip_horizontal();
+            transformed.flip_horizontal();
+            transformed.flip_vertical().unwrap();
+            transformed.flip_vertical().unwrap();
+            assert_eq!(transformed, fpga);
+        }
+
+        let mut fpga = FPGA::new(3, 2);
+        fpga.get_mut(0, 0).unwrap().flags |= CellFlags::JC1_R1;
+        fpga.rotate_90().unwrap();
+        assert_eq!(fpga.dimensions(), (2, 3));
+        assert_eq!(
+            fpga.get_cell(2, 0).unwrap().flags.junctions(),
+            CellFlags::JC1_R2
+        );
+        let junctions = fpga
+            .cells()
+            .filter(|cell| !cell.flags.junctions().is_empty());
+        assert_eq!(junctions.count(), 1);
+
+        let fpga =
+            FPGA::from_cells(2, 2, vec![negate(), copy(), Cell::default(), negate()]).unwrap();
+        let mut rotated = fpga.clone();
+        assert_eq!(rotated.rotate_90(), Err("Only columns can be negated"));
+        assert_eq!(rotated, fpga);
+        assert_eq!(rotated.flip_vertical(), Err("The NOTs always feed Row 1"));
+        assert_eq!(rotated, fpga);
+        rotated.flip_horizontal();
+        rotated.flip_horizontal();
+        assert_eq!(rotated, fpga);
+    }
+
+    #[test]
+    fn eval_with_progress() {
+        let fpga = FPGA::new(3, 4);
+
+        let mut rows = Vec::new();
+        let output = fpga.eval_with_progress(FpgaIO::new(6), |row| {
+            rows.push(row);
+            ControlFlow::Continue(())
+        });
+        assert!(output.is_ok());
+        assert_eq!(rows, vec![1, 2, 3, 4]);
+
+        let output = fpga.eval_with_progress(FpgaIO::new(6), |row| {
+            if row == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(output.unwrap_err(), EvalError::Cancelled);
+    }
+
+    #[test]
+    fn eval_with_probes() {
+        let mut fpga = FPGA::new(2, 2);
+
+        // A cell whose column 1 always fires.
+        let probed = fpga.get_mut(1, 0).unwrap();
+        probed.flags.set(CellFlags::C1_OUT, true);
+        probed.fills = Fills::new(4, 0, 0, 0);
+
+        let input = FpgaIO::new(4);
+        let (output, values) = fpga
+            .eval_with_probes(input.clone(), &[(1, 0), (0, 0), (1, 0)])
+            .unwrap();
+
+        assert_eq!(
+            output.to_string(),
+            fpga.eval(input.clone()).unwrap().to_string()
+        );
+        assert_eq!(values.len(), 3);
+        assert!(values[0].contains(CellIO::COLUMN_1));
+        assert_eq!(values[0], values[2]);
+        assert!(!values[1].contains(CellIO::COLUMN_1));
+
+        assert_eq!(
+            fpga.eval_with_probes(input, &[(0, 0), (2, 0)]).unwrap_err(),
+            EvalError::ProbeOutOfBounds { row: 2, col: 0 }
+        );
+    }
+
+    #[test]
+    fn eval_trace() {
+        let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+
+        for _ in 0..20 {
+            let fpga = rng.fpga();
+            if fpga.is_empty() {
+                continue;
+            }
+            let input = FpgaIO::new(fpga.width() * 2);
+            let probes: Vec<_> = (0..fpga.height())
+                .flat_map(|row| (0..fpga.width()).map(move |col| (row, col)))
+                .collect();
+
+            let (output, trace) = fpga.eval_trace(input.clone()).unwrap();
+            let (expected, values) = fpga.eval_with_probes(input, &probes).unwrap();
+            assert_eq!(output, expected);
+            assert_eq!(trace, values);
+        }
+
+        assert_eq!(
+            FPGA::new(2, 2).eval_trace(FpgaIO::new(2)).unwrap_err(),
+            EvalError::SizeMismatch
+        );
+    }
+
+    #[test]
+    fn eval_sequential() {
+        let fpga = FPGA::new(2, 2);
+        let outputs = fpga.eval_sequential(FpgaIO::new(4), 10).unwrap();
+        assert_eq!(outputs, vec![FpgaIO::new(4)]);
+
+        // Column 1 of the last row always fires, so the board
+        // settles once that bit has been fed back once.
+        let mut fpga = FPGA::new(2, 2);
+        let cell = fpga.get_mut(1, 0).unwrap();
+        cell.flags.set(CellFlags::C1_OUT, true);
+        cell.fills = Fills::new(4, 0, 0, 0);
+
+        let outputs = fpga.eval_sequential(FpgaIO::new(4), 10).unwrap();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0], outputs[1]);
+        assert_eq!(outputs[0].get_value_vec()[..], [true, false, false, false]);
+
+        assert!(fpga.eval_sequential(FpgaIO::new(4), 0).unwrap().is_empty());
+        assert_eq!(
+            fpga.eval_sequential(FpgaIO::new(3), 10),
+            Err(EvalError::SizeMismatch)
+        );
+
+        let mut rng = Rng(0xD1B5_4A32_D192_ED03);
+        for _ in 0..50 {
+            let fpga = rng.fpga();
+            if fpga.is_empty() {
+                continue;
+            }
+            let mut input = FpgaIO::new(fpga.width() * 2);
+            let outputs = fpga.eval_sequential(input.clone(), 8).unwrap();
+
+            for (i, output) in outputs.iter().enumerate() {
+                assert_eq!(&fpga.eval(input.clone()).unwrap(), output);
+                if *output == input || outputs.len() < 8 {
+                    assert_eq!(*output == input, i + 1 == outputs.len());
+                }
+                input = output.clone();
+            }
+        }
+    }
+
+    fn cell(order: [Selector; 4], flags: CellFlags, fills: Fills) -> Cell {
+        Cell::new(&ActivationOrder::new(order).unwrap(), &flags, fills)
+    }
+
+    /// Column 1 becomes the negation of column 2, which is
+    /// carried through.
+    fn negate() -> Cell {
+        cell(
+            [
+                Selector::Column2,
+                Selector::Row1,
+                Selector::Column1,
+                Selector::Row2,
+            ],
+            CellFlags::JC2_R1 | CellFlags::C2_OUT | CellFlags::R1_OUT | CellFlags::NOT_C1,
+            Fills::new(0, 1, 1, 0),
+        )
+    }
+
+    /// Column 1 is carried through and copied into column 2
+    /// through both rows, with column 2 being its negation.
+    fn copy() -> Cell {
+        cell(
+            [
+                Selector::Column1,
+                Selector::Row1,
+                Selector::Row2,
+                Selector::Column2,
+            ],
+            CellFlags::JC1_R1
+                | CellFlags::JC1_R2
+                | CellFlags::JC2_R1
+                | CellFlags::JC2_R2
+                | CellFlags::C2_OUT,
+            Fills::new(1, 1, 2, 2),
+        )
+    }
+
+    /// A 2x2 board whose first column flips both its bits at
+    /// every step when its output is fed back, the second column
+    /// is left to the default cells.
+    fn oscillator() -> FPGA {
+        FPGA::from_cells(
+            2,
+            2,
+            vec![negate(), Cell::default(), copy(), Cell::default()],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn eval_golden() {
+        // Both columns are joined to Row 1, with Column 2
+        // as an output.
+        let and = cell(
+            [
+                Selector::Column1,
+                Selector::Column2,
+                Selector::Row1,
+                Selector::Row2,
+            ],
+            CellFlags::JC1_R1 | CellFlags::JC2_R1 | CellFlags::C2_OUT,
+            Fills::new(0, 1, 0, 0),
+        );
+        // The second row is swept right to left, after the
+        // direction flip at the end of the first one.
+        let fpga =
+            FPGA::from_cells(3, 2, vec![copy(), negate(), and, negate(), and, copy()]).unwrap();
+
+        // The output of every input, as in FpgaIO::as_u64.
+        #[rustfmt::skip]
+        const GOLDEN: [u64; 64] = [
+            1, 2, 1, 2, 1, 2, 1, 2, 9, 10, 9, 10, 9, 10, 9, 10,
+            1, 2, 1, 2, 1, 2, 1, 2, 9, 10, 9, 10, 9, 10, 9, 10,
+            1, 2, 1, 2, 1, 2, 1, 2, 9, 10, 9, 10, 9, 10, 9, 10,
+            1, 2, 1, 2, 1, 2, 1, 2, 9, 10, 9, 10, 9, 10, 9, 10,
+        ];
+
+        for (input, expected) in GOLDEN.iter().enumerate() {
+            let output = fpga.eval(FpgaIO::from_u64(6, input as u64)).unwrap();
+            assert_eq!(output.as_u64(), Some(*expected), "input {input:06b}");
+        }
+    }
+
+    #[test]
+    fn eval_until_stable() {
+        let fpga = oscillator();
+        let zero = FpgaIO::new(4);
+        let ones: FpgaIO = "1100".parse().unwrap();
+        assert_eq!(fpga.eval(zero.clone()), Ok(ones.clone()));
+        assert_eq!(fpga.eval(ones), Ok(zero.clone()));
+
+        assert_eq!(
+            fpga.eval_until_stable(zero.clone(), 10),
+            Ok(Stability::Oscillates { period: 2 })
+        );
+        assert_eq!(fpga.eval_sequential(zero.clone(), 10).unwrap().len(), 10);
+        assert_eq!(
+            fpga.eval_until_stable(zero.clone(), 2),
+            Ok(Stability::DidNotSettle)
+        );
+
+        assert_eq!(
+            FPGA::new(2, 2).eval_until_stable(zero.clone(), 10),
+            Ok(Stability::Settled(zero))
+        );
+        assert_eq!(
+            fpga.eval_until_stable(FpgaIO::new(2), 10),
+            Err(EvalError::SizeMismatch)
+        );
     }
 }
@@ -0,0 +1,599 @@
+//! A line-oriented text format describing an [FPGA].
+//!
+//! The first line gives the dimensions as `size <width>x<height>`,
+//! every other line configures the cell at `row,col`, with the
+//! cells that aren't listed keeping the default configuration:
+//!
+//! ```text
+//! # A 4 columns by 3 rows board.
+//! size 4x3
+//! cell 2,3: order=C1 R1 C2 R2; not c1; out c1 r2; junction c1 r1; fill r1=2
+//! cell 0,0: disabled
+//! ```
+//!
+//! The items of a cell are separated by `;`:
+//!
+//! - `order=<4 lines>` sets the activation order.
+//! - `not <columns>` negates the given columns.
+//! - `out <lines>` enables the outputs of the given lines.
+//! - `junction <column> <row>` connects a column and a row.
+//! - `fill <line>=<amount> ...` sets the filler blocks of the lines.
+//! - `disabled` turns it into a ghost cell.
+//!
+//! Everything after a `#` is a comment, and both the keywords and
+//! the line names ignore their case.
+//!
+//! [to_schematic] writes a board back in this format.
+
+use crate::FPGA;
+use crate::cell::{ActivationOrder, Cell, CellFlags, Fills, LineArray, Selector};
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+use core::fmt::Write;
+
+/// The most cells a schematic can describe, so that the size
+/// line of an untrusted one can't allocate an unbounded board.
+pub const MAX_CELLS: usize = 1 << 24;
+
+/// The reason why a schematic couldn't be parsed, together
+/// with where it was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// The 1-based line of the error.
+    pub line: usize,
+    /// The 1-based column of the error.
+    pub column: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// A piece of a line, remembering where it starts.
+#[derive(Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    /// The 0-based byte offset of `text` in its line.
+    offset: usize,
+}
+
+impl<'a> Token<'a> {
+    /// Returns the part of the token after `start` bytes.
+    fn slice(self, start: usize) -> Token<'a> {
+        Token {
+            text: &self.text[start..],
+            offset: self.offset + start,
+        }
+    }
+
+    fn trim(self) -> Token<'a> {
+        let start = self.text.len() - self.text.trim_start().len();
+        let token = self.slice(start);
+        Token {
+            text: token.text.trim_end(),
+            ..token
+        }
+    }
+
+    /// Splits the token at the first `separator`, which is dropped.
+    fn split_once(self, separator: char) -> Option<(Token<'a>, Token<'a>)> {
+        let index = self.text.find(separator)?;
+        let head = Token {
+            text: &self.text[..index],
+            offset: self.offset,
+        };
+
+        Some((head, self.slice(index + separator.len_utf8())))
+    }
+
+    fn split(self, separator: char) -> impl Iterator<Item = Token<'a>> {
+        self.text.split(separator).map(move |text| Token {
+            text,
+            offset: self.offset + (text.as_ptr() as usize - self.text.as_ptr() as usize),
+        })
+    }
+
+    fn words(self) -> impl Iterator<Item = Token<'a>> {
+        self.text.split_whitespace().map(move |text| Token {
+            text,
+            offset: self.offset + (text.as_ptr() as usize - self.text.as_ptr() as usize),
+        })
+    }
+}
+
+/// Reads the lines of a schematic, keeping track of where
+/// the errors are found.
+struct Parser {
+    line: usize,
+}
+
+impl Parser {
+    fn error(&self, token: Token, message: &'static str) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: token.offset + 1,
+            message,
+        }
+    }
+
+    fn number<T: core::str::FromStr>(&self, token: Token) -> Result<T, ParseError> {
+        token
+            .text
+            .parse()
+            .map_err(|_| self.error(token, "Expected a number"))
+    }
+
+    fn selector(&self, token: Token) -> Result<Selector, ParseError> {
+        token.text.parse().map_err(|err| self.error(token, err))
+    }
+
+    /// Parses `size <width>x<height>`, given the part after `size`,
+    /// rejecting boards of more than [MAX_CELLS] cells.
+    fn size(&self, args: Token) -> Result<(usize, usize), ParseError> {
+        let args = args.trim();
+        let (width, height) = args
+            .split_once('x')
+            .ok_or_else(|| self.error(args, "Expected the size as <width>x<height>"))?;
+        let width: usize = self.number(width.trim())?;
+        let height: usize = self.number(height.trim())?;
+
+        match width.checked_mul(height) {
+            Some(cells) if cells <= MAX_CELLS => Ok((width, height)),
+            _ => Err(self.error(args, "The board has too many cells")),
+        }
+    }
+
+    /// Parses `<row>,<col>: <items>`, given the part after `cell`.
+    fn cell(&self, args: Token) -> Result<((usize, usize), Cell), ParseError> {
+        let args = args.trim();
+        let (position, items) = args
+            .split_once(':')
+            .ok_or_else(|| self.error(args, "Expected `:` after the cell position"))?;
+        let (row, col) = position
+            .trim()
+            .split_once(',')
+            .ok_or_else(|| self.error(position.trim(), "Expected the position as <row>,<col>"))?;
+        let position = (self.number(row.trim())?, self.number(col.trim())?);
+
+        let mut cell = Cell::default();
+        let mut fills = LineArray::default();
+
+        for item in items.split(';').map(Token::trim) {
+            if item.text.is_empty() {
+                continue;
+            }
+
+            let end = item
+                .text
+                .find(|c: char| c.is_whitespace() || c == '=')
+                .unwrap_or(item.text.len());
+            let keyword = Token {
+                text: &item.text[..end],
+                offset: item.offset,
+            };
+            let mut rest = item.slice(end).trim();
+            if let Some(after) = rest.text.strip_prefix('=') {
+                rest = rest.slice(rest.text.len() - after.len());
+            }
+            let args: Vec<Token> = rest.words().collect();
+
+            match keyword.text.to_ascii_lowercase().as_str() {
+                "order" => cell.activation_order = self.order(item, &args)?,
+                "not" => {
+                    for arg in self.some(item, &args)? {
+                        cell.flags |= match self.selector(*arg)? {
+                            Selector::Column1 => CellFlags::NOT_C1,
+                            Selector::Column2 => CellFlags::NOT_C2,
+                            _ => return Err(self.error(*arg, "Only columns can be negated")),
+                        };
+                    }
+                }
+                "out" => {
+                    for arg in self.some(item, &args)? {
+                        cell.flags |= match self.selector(*arg)? {
+                            Selector::Column1 => CellFlags::C1_OUT,
+                            Selector::Column2 => CellFlags::C2_OUT,
+                            Selector::Row1 => CellFlags::R1_OUT,
+                            Selector::Row2 => CellFlags::R2_OUT,
+                        };
+                    }
+                }
+                "junction" => cell.flags |= self.junction(item, &args)?,
+                "fill" => {
+                    for arg in self.some(item, &args)? {
+                        let (line, amount) = arg
+                            .split_once('=')
+                            .ok_or_else(|| self.error(*arg, "Expected <line>=<amount>"))?;
+                        let amount: u8 = self.number(amount)?;
+                        if amount > Fills::MAX {
+                            return Err(self.error(*arg, "Too many filler blocks"));
+                        }
+                        fills[self.selector(line)?] = amount;
+                    }
+                }
+                "disabled" => {
+                    if let Some(arg) = args.first() {
+                        return Err(self.error(*arg, "`disabled` takes no arguments"));
+                    }
+                    cell.flags |= CellFlags::DISABLED;
+                }
+                _ => return Err(self.error(keyword, "Unknown cell item")),
+            }
+        }
+
+        cell.fills = Fills::from(fills);
+
+        Ok((position, cell))
+    }
+
+    /// Checks that at least one argument was given to `item`.
+    fn some<'t, 'a>(
+        &self,
+        item: Token,
+        args: &'t [Token<'a>],
+    ) -> Result<&'t [Token<'a>], ParseError> {
+        if args.is_empty() {
+            return Err(self.error(item, "Expected at least one line"));
+        }
+        Ok(args)
+    }
+
+    fn order(&self, item: Token, args: &[Token]) -> Result<ActivationOrder, ParseError> {
+        let [a, b, c, d] = args else {
+            return Err(self.error(item, "Expected 4 lines in activation order"));
+        };
+        let order = [
+            self.selector(*a)?,
+            self.selector(*b)?,
+            self.selector(*c)?,
+            self.selector(*d)?,
+        ];
+
+        ActivationOrder::new(order).map_err(|err| self.error(item, err))
+    }
+
+    fn junction(&self, item: Token, args: &[Token]) -> Result<CellFlags, ParseError> {
+        let [column, row] = args else {
+            return Err(self.error(item, "Expected a column and a row"));
+        };
+
+        match (self.selector(*column)?, self.selector(*row)?) {
+            (Selector::Column1, Selector::Row1) => Ok(CellFlags::JC1_R1),
+            (Selector::Column1, Selector::Row2) => Ok(CellFlags::JC1_R2),
+            (Selector::Column2, Selector::Row1) => Ok(CellFlags::JC2_R1),
+            (Selector::Column2, Selector::Row2) => Ok(CellFlags::JC2_R2),
+            _ => Err(self.error(*column, "Expected a column and a row")),
+        }
+    }
+}
+
+/// Parses a schematic, see the [module](self) docs for the format.
+///
+/// ## Returns
+///
+/// - [Ok(FPGA)] with the described board.
+/// - [Err(ParseError)] pointing at the first malformed token,
+///   a cell outside the board or a cell configured twice.
+pub fn parse_schematic(src: &str) -> Result<FPGA, ParseError> {
+    let mut parser = Parser { line: 0 };
+    let mut fpga: Option<FPGA> = None;
+    let mut configured = Vec::new();
+
+    for (i, line) in src.lines().enumerate() {
+        parser.line = i + 1;
+
+        let line = line.split('#').next().unwrap_or_default();
+        let line = Token {
+            text: line,
+            offset: 0,
+        }
+        .trim();
+        let Some(keyword) = line.words().next() else {
+            continue;
+        };
+        let args = line.slice(keyword.text.len());
+
+        match (keyword.text.to_ascii_lowercase().as_str(), &mut fpga) {
+            ("size", None) => {
+                let (width, height) = parser.size(args)?;
+                configured = vec![false; width * height];
+                fpga = Some(FPGA::new(width, height));
+            }
+            ("size", Some(_)) => return Err(parser.error(keyword, "The size is already set")),
+            ("cell", Some(fpga)) => {
+                let ((row, col), cell) = parser.cell(args)?;
+                let width = fpga.width();
+                let Some(target) = fpga.get_mut(row, col) else {
+                    return Err(parser.error(args.trim(), "Cell outside the board"));
+                };
+                if core::mem::replace(&mut configured[row * width + col], true) {
+                    return Err(parser.error(args.trim(), "Cell already configured"));
+                }
+                *target = cell;
+            }
+            ("cell", None) => return Err(parser.error(keyword, "Expected the size first")),
+            _ => return Err(parser.error(keyword, "Expected `size` or `cell`")),
+        }
+    }
+
+    fpga.ok_or(ParseError {
+        line: parser.line.max(1),
+        column: 1,
+        message: "Missing the board size",
+    })
+}
+
+/// The lines in the order they're written in a schematic.
+const LINES: [Selector; 4] = [
+    Selector::Column1,
+    Selector::Column2,
+    Selector::Row1,
+    Selector::Row2,
+];
+
+/// Writes `fpga` as a schematic that [parse_schematic] reads
+/// back, leaving out the cells with the default configuration.
+///
+/// Only the configuration is written, the simulation-only
+/// flags of the cells are always read back as their default.
+pub fn to_schematic(fpga: &FPGA) -> String {
+    let mut src = format!("size {}x{}\n", fpga.width(), fpga.height());
+
+    for row in 0..fpga.height() {
+        for col in 0..fpga.width() {
+            let cell = fpga.get_cell(row, col).expect("Cell inside the FPGA");
+            if !cell.is_default() {
+                let _ = writeln!(src, "cell {row},{col}: {}", cell_items(cell).join("; "));
+            }
+        }
+    }
+
+    src
+}
+
+/// Describes `cell` as the items of its `cell` line.
+fn cell_items(cell: &Cell) -> Vec<String> {
+    let flags = cell.flags;
+    let names = |lines: &[(CellFlags, Selector)]| {
+        let names: Vec<_> = lines
+            .iter()
+            .filter(|(flag, _)| flags.contains(*flag))
+            .map(|(_, line)| line.to_string())
+            .collect();
+        names.join(" ")
+    };
+    let mut items = Vec::new();
+
+    if cell.activation_order != ActivationOrder::default() {
+        let order: Vec<_> = cell
+            .activation_order
+            .into_iter()
+            .map(|line| line.to_string())
+            .collect();
+        items.push(format!("order={}", order.join(" ")));
+    }
+
+    if !flags.nots().is_empty() {
+        items.push(format!(
+            "not {}",
+            names(&[
+                (CellFlags::NOT_C1, Selector::Column1),
+                (CellFlags::NOT_C2, Selector::Column2),
+            ])
+        ));
+    }
+
+    if !flags.outputs().is_empty() {
+        items.push(format!(
+            "out {}",
+            names(&[
+                (CellFlags::C1_OUT, Selector::Column1),
+                (CellFlags::C2_OUT, Selector::Column2),
+                (CellFlags::R1_OUT, Selector::Row1),
+                (CellFlags::R2_OUT, Selector::Row2),
+            ])
+        ));
+    }
+
+    let junctions = [
+        (CellFlags::JC1_R1, Selector::Column1, Selector::Row1),
+        (CellFlags::JC1_R2, Selector::Column1, Selector::Row2),
+        (CellFlags::JC2_R1, Selector::Column2, Selector::Row1),
+        (CellFlags::JC2_R2, Selector::Column2, Selector::Row2),
+    ];
+    for (flag, column, row) in junctions {
+        if flags.contains(flag) {
+            items.push(format!("junction {column} {row}"));
+        }
+    }
+
+    let fills: Vec<_> = LINES
+        .into_iter()
+        .filter(|line| cell.fills.get_line(*line) != 0)
+        .map(|line| format!("{line}={}", cell.fills.get_line(line)))
+        .collect();
+    if !fills.is_empty() {
+        items.push(format!("fill {}", fills.join(" ")));
+    }
+
+    if flags.contains(CellFlags::DISABLED) {
+        items.push("disabled".to_owned());
+    }
+
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::Rng;
+
+    #[test]
+    fn parse() {
+        let fpga = parse_schematic(
+            "# A small board.
+            size 4x3
+
+            cell 2,3: order=C1 R1 C2 R2; not c1; out c1 r2; junction c1 r1; fill r1=2
+            CELL 0,0 : Disabled ; junction C2 R2 # A ghost cell.
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(fpga.dimensions(), (4, 3));
+        assert_eq!(fpga.used_cell_count(), 2);
+
+        let cell = fpga.get_cell(2, 3).unwrap();
+        assert_eq!(
+            cell.activation_order,
+            ActivationOrder::new([
+                Selector::Column1,
+                Selector::Row1,
+                Selector::Column2,
+                Selector::Row2,
+            ])
+            .unwrap()
+        );
+        assert_eq!(
+            cell.flags,
+            CellFlags::default()
+                | CellFlags::NOT_C1
+                | CellFlags::C1_OUT
+                | CellFlags::R2_OUT
+                | CellFlags::JC1_R1
+        );
+        assert_eq!(cell.fills, Fills::new(0, 0, 2, 0));
+
+        let cell = fpga.get_cell(0, 0).unwrap();
+        assert_eq!(
+            cell.flags,
+            CellFlags::default() | CellFlags::DISABLED | CellFlags::JC2_R2
+        );
+        assert_eq!(fpga.validate(), Ok(()));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |src: &str| {
+            let err = parse_schematic(src).unwrap_err();
+            (err.line, err.column, err.message)
+        };
+
+        assert_eq!(error(""), (1, 1, "Missing the board size"));
+        assert_eq!(error("cell 0,0: out c1"), (1, 1, "Expected the size first"));
+        assert_eq!(
+            error("size 2y3"),
+            (1, 6, "Expected the size as <width>x<height>")
+        );
+        assert_eq!(
+            error(&format!("size {}x2", usize::MAX)),
+            (1, 6, "The board has too many cells")
+        );
+        assert_eq!(
+            error("size 100000x100000"),
+            (1, 6, "The board has too many cells")
+        );
+        assert_eq!(
+            error("size 2x3\nsize 2x3"),
+            (2, 1, "The size is already set")
+        );
+        assert_eq!(error("size 2x3\nwire"), (2, 1, "Expected `size` or `cell`"));
+        assert_eq!(
+            error("size 2x3\ncell 0,a: out c1"),
+            (2, 8, "Expected a number")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 0,0 out c1"),
+            (2, 6, "Expected `:` after the cell position")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 3,0: out c1"),
+            (2, 6, "Cell outside the board")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 1,1: out c1\ncell 1,1: out c2"),
+            (3, 6, "Cell already configured")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 0,0: out c3"),
+            (2, 15, "Unknown line, expected one of C1, C2, R1 or R2")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 0,0: out"),
+            (2, 11, "Expected at least one line")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 0,0: not r1"),
+            (2, 15, "Only columns can be negated")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 0,0: order=C1 R1 C2"),
+            (2, 11, "Expected 4 lines in activation order")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 0,0: order=C1 R1 C2 C1"),
+            (2, 11, "Duplicate enum variants not allowed")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 0,0: junction r1 c1"),
+            (2, 20, "Expected a column and a row")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 0,0: fill c1=999"),
+            (2, 19, "Expected a number")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 0,0: fill c1=250"),
+            (2, 16, "Too many filler blocks")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 0,0: fill c1"),
+            (2, 16, "Expected <line>=<amount>")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 0,0: glow"),
+            (2, 11, "Unknown cell item")
+        );
+        assert_eq!(
+            error("size 2x3\ncell 0,0: disabled now"),
+            (2, 20, "`disabled` takes no arguments")
+        );
+    }
+
+    #[test]
+    fn write() {
+        let src = "size 4x3
+cell 0,0: junction C2 R2; disabled
+cell 2,3: order=C1 R1 C2 R2; not C1; out C1 R2; junction C1 R1; fill R1=2
+";
+        let fpga = parse_schematic(src).unwrap();
+        assert_eq!(to_schematic(&fpga), src);
+        assert_eq!(to_schematic(&FPGA::new(2, 1)), "size 2x1\n");
+    }
+
+    #[test]
+    fn write_round_trip_random() {
+        let mut rng = Rng(0xA076_1D64_78BD_642F);
+
+        for _ in 0..200 {
+            let mut fpga = rng.fpga();
+            // The simulation-only flags aren't part of the schematic.
+            for row in 0..fpga.height() {
+                for col in 0..fpga.width() {
+                    let flags = &mut fpga.get_mut(row, col).unwrap().flags;
+                    *flags = flags.difference(CellFlags::default()) | CellFlags::default();
+                }
+            }
+
+            assert_eq!(parse_schematic(&to_schematic(&fpga)), Ok(fpga));
+        }
+    }
+}
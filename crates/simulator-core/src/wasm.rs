@@ -0,0 +1,56 @@
+//! JavaScript bindings, made with `wasm-bindgen`.
+//!
+//! Bits cross the boundary as a `Uint8Array` of one byte per
+//! bit, with any non-zero byte being set, just like the
+//! functions of the `ffi` module take them.
+
+use crate::{FPGA, FpgaIO};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use wasm_bindgen::prelude::*;
+
+/// Evaluates the board encoded by [FPGA::to_bytes] with `input`,
+/// returning the output bits.
+///
+/// ## Returns
+///
+/// - [Err(JsError)] if `board` is malformed or the
+///   evaluation fails, see [FPGA::eval].
+#[wasm_bindgen]
+pub fn evaluate(board: &[u8], input: &[u8]) -> Result<Vec<u8>, JsError> {
+    evaluate_bits(board, input).map_err(|err| JsError::new(&err))
+}
+
+/// The body of [evaluate], kept apart since a [JsError]
+/// can only be made on a `wasm` target.
+fn evaluate_bits(board: &[u8], input: &[u8]) -> Result<Vec<u8>, String> {
+    let fpga = FPGA::try_from(board).map_err(ToString::to_string)?;
+    let input: Vec<bool> = input.iter().map(|&bit| bit != 0).collect();
+    let output = fpga
+        .eval(FpgaIO::from(input.into_boxed_slice()))
+        .map_err(|err| err.to_string())?;
+
+    Ok(output.iter_bits().map(u8::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EvalError;
+
+    #[test]
+    fn evaluate_bits() {
+        let fpga = FPGA::new(2, 2);
+        let input = [1, 0, 7, 0];
+
+        let expected = fpga.eval(FpgaIO::from_u64(4, 0b0101)).unwrap();
+        let expected: Vec<u8> = expected.iter_bits().map(u8::from).collect();
+        assert_eq!(super::evaluate_bits(&fpga.to_bytes(), &input), Ok(expected));
+
+        assert_eq!(
+            super::evaluate_bits(&fpga.to_bytes(), &input[..3]),
+            Err(EvalError::SizeMismatch.to_string())
+        );
+        assert!(super::evaluate_bits(&[0xFF], &input).is_err());
+    }
+}
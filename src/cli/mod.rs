@@ -1,5 +1,616 @@
-pub struct CLI {}
+use crate::export;
+use crate::io::File;
+use crate::theme::ViewerTheme;
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand, ValueEnum};
+use simulator_core::cell::{ActivationOrder, Cell, CellFlags, CellIO, Selector};
+use simulator_core::diff::{CellDiff, diff};
+use simulator_core::{FPGA, FpgaIO};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Ghost Block FPGA Simulator")]
+pub struct CLI {
+    /// Runs the given command without any UI,
+    /// the GUI is opened when none is given.
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum Command {
+    /// Creates an FPGA configuration file with a blank board.
+    New {
+        /// The FPGA configuration file to create.
+        #[arg(long)]
+        file: PathBuf,
+        /// The amount of cells in each row.
+        #[arg(long)]
+        width: usize,
+        /// The amount of rows.
+        #[arg(long)]
+        height: usize,
+    },
+    /// Loads an FPGA configuration file, evaluates it and
+    /// prints the output bits.
+    Eval {
+        /// The FPGA configuration file to evaluate.
+        #[arg(long)]
+        file: PathBuf,
+        /// The column input bits, e.g. `0110`.
+        #[arg(long)]
+        input: String,
+        /// Pads a shorter input with `0` bits and accepts a longer
+        /// one as long as only `0` bits are dropped.
+        #[arg(long)]
+        pad: bool,
+    },
+    /// Loads an FPGA configuration file and prints, as CSV, the
+    /// output bits of the whole board for every possible input.
+    TruthTable {
+        /// The FPGA configuration file to characterize.
+        #[arg(long)]
+        file: PathBuf,
+    },
+    /// Loads an FPGA configuration file and prints, as CSV, the
+    /// output lines of a single cell for every possible input.
+    CellTruthTable {
+        /// The FPGA configuration file containing the cell.
+        #[arg(long)]
+        file: PathBuf,
+        /// The row of the cell.
+        #[arg(long)]
+        row: usize,
+        /// The column of the cell.
+        #[arg(long)]
+        col: usize,
+    },
+    /// Prints the size of the design in an FPGA configuration file.
+    Stats {
+        /// The FPGA configuration file to inspect.
+        #[arg(long)]
+        file: PathBuf,
+    },
+    /// Prints the position of every cell with the given flag on.
+    Search {
+        /// The FPGA configuration file to search.
+        #[arg(long)]
+        file: PathBuf,
+        /// The flag to look for, e.g. `NOT_C1`.
+        flag: String,
+    },
+    /// Compares two FPGA configuration files and prints the
+    /// cells that differ between them.
+    Diff {
+        /// The original FPGA configuration file.
+        old: PathBuf,
+        /// The changed FPGA configuration file.
+        new: PathBuf,
+    },
+    /// Turns a flag of a cell on or off and saves the file.
+    Set {
+        /// The FPGA configuration file to edit.
+        #[arg(long)]
+        file: PathBuf,
+        /// The row of the cell.
+        #[arg(long)]
+        row: usize,
+        /// The column of the cell.
+        #[arg(long)]
+        col: usize,
+        /// The flag to change, e.g. `NOT_C1`.
+        flag: String,
+        state: Switch,
+    },
+    /// Sets the activation order of a cell and saves the file.
+    Order {
+        /// The FPGA configuration file to edit.
+        #[arg(long)]
+        file: PathBuf,
+        /// The row of the cell.
+        #[arg(long)]
+        row: usize,
+        /// The column of the cell.
+        #[arg(long)]
+        col: usize,
+        /// The 4 lines in activation order, e.g. `C1 R1 C2 R2`.
+        #[arg(required = true)]
+        order: Vec<Selector>,
+    },
+    /// Sets the label of a cell, or removes it when no text
+    /// is given, and saves the file.
+    Label {
+        /// The FPGA configuration file to edit.
+        #[arg(long)]
+        file: PathBuf,
+        /// The row of the cell.
+        #[arg(long)]
+        row: usize,
+        /// The column of the cell.
+        #[arg(long)]
+        col: usize,
+        /// The text of the label.
+        text: Option<String>,
+    },
+    /// Copies the configuration of a cell into another one
+    /// and saves the file.
+    Copy {
+        /// The FPGA configuration file to edit.
+        #[arg(long)]
+        file: PathBuf,
+        /// The cell to copy, as `row,col`.
+        from: Position,
+        /// The cell to overwrite, as `row,col`.
+        to: Position,
+    },
+    /// Copies the configuration of a cell into a whole
+    /// rectangular region and saves the file.
+    Fill {
+        /// The FPGA configuration file to edit.
+        #[arg(long)]
+        file: PathBuf,
+        /// The cell to copy, as `row,col`.
+        #[arg(long)]
+        cell: Position,
+        /// The first corner of the region, as `row,col`.
+        top_left: Position,
+        /// The opposite, inclusive, corner of the region.
+        bottom_right: Position,
+    },
+    /// Prints an FPGA configuration file as text, followed
+    /// by the labels of its cells.
+    Show {
+        /// The FPGA configuration file to print.
+        #[arg(long)]
+        file: PathBuf,
+        /// A cell to highlight, as `row,col`.
+        #[arg(long)]
+        select: Option<Position>,
+    },
+    /// Renders an FPGA configuration file as an SVG image.
+    Svg {
+        /// The FPGA configuration file to render.
+        #[arg(long)]
+        file: PathBuf,
+        /// The SVG file to write.
+        output: PathBuf,
+    },
+    /// Saves the truth table of every cell in an FPGA configuration
+    /// file, so that it loads ready to simulate, and saves it.
+    Compile {
+        /// The FPGA configuration file to compile.
+        #[arg(long)]
+        file: PathBuf,
+        /// Removes the truth tables instead.
+        #[arg(long)]
+        strip: bool,
+    },
+    /// Resets every cell to the default configuration, keeping
+    /// the dimensions of the FPGA, and saves the file.
+    Reset {
+        /// The FPGA configuration file to reset.
+        #[arg(long)]
+        file: PathBuf,
+    },
+}
+
+/// A `(row, col)` cell position, written as `row,col`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Position(usize, usize);
+
+impl FromStr for Position {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (row, col) = s.split_once(',').ok_or("Expected a position as row,col")?;
+        let parse = |value: &str| value.trim().parse().map_err(|_| "Invalid position");
+
+        Ok(Self(parse(row)?, parse(col)?))
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum Switch {
+    On,
+    Off,
+}
 
 impl CLI {
-    fn run() {}
+    /// The maximum amount of input bits a truth table is
+    /// generated for, every extra bit doubles its rows.
+    const MAX_TRUTH_TABLE_INPUTS: usize = 20;
+
+    pub fn run(command: Command) -> Result<()> {
+        match command {
+            Command::New {
+                file,
+                width,
+                height,
+            } => Self::new_file(file, width, height),
+            Command::Eval { file, input, pad } => Self::eval(file, &input, pad),
+            Command::TruthTable { file } => Self::truth_table(file),
+            Command::CellTruthTable { file, row, col } => Self::cell_truth_table(file, row, col),
+            Command::Stats { file } => Self::stats(file),
+            Command::Search { file, flag } => Self::search(file, &flag),
+            Command::Diff { old, new } => Self::diff(old, new),
+            Command::Set {
+                file,
+                row,
+                col,
+                flag,
+                state,
+            } => Self::set(file, row, col, &flag, state),
+            Command::Order {
+                file,
+                row,
+                col,
+                order,
+            } => Self::order(file, row, col, &order),
+            Command::Label {
+                file,
+                row,
+                col,
+                text,
+            } => Self::label(file, row, col, text),
+            Command::Copy { file, from, to } => {
+                Self::edit(file, |fpga| fpga.copy_cell((from.0, from.1), (to.0, to.1)))
+            }
+            Command::Fill {
+                file,
+                cell,
+                top_left,
+                bottom_right,
+            } => Self::edit(file, |fpga| {
+                let cell = *fpga
+                    .get_cell(cell.0, cell.1)
+                    .ok_or("Source cell out of bounds")?;
+                fpga.fill_region(
+                    (top_left.0, top_left.1),
+                    (bottom_right.0, bottom_right.1),
+                    cell,
+                )
+            }),
+            Command::Show { file, select } => Self::show(file, select),
+            Command::Svg { file, output } => Self::svg(file, output),
+            Command::Compile { file, strip } => Self::compile(file, strip),
+            Command::Reset { file } => Self::edit(file, |fpga| {
+                *fpga = FPGA::new(fpga.width(), fpga.height());
+                Ok(())
+            }),
+        }
+    }
+
+    fn load_file(path: PathBuf) -> Result<File> {
+        let mut file = File::default();
+        file.set_path(Some(path));
+        file.load_fpga()?;
+
+        Ok(file)
+    }
+
+    fn load(path: PathBuf) -> Result<FPGA> {
+        Ok(Self::load_file(path)?.fpga)
+    }
+
+    fn new_file(path: PathBuf, width: usize, height: usize) -> Result<()> {
+        File::validate_dimensions(width, height).map_err(anyhow::Error::msg)?;
+        if path.exists() {
+            bail!("{} already exists", path.display());
+        }
+
+        let mut file = File::default();
+        file.new_fpga(width, height);
+        file.set_path(Some(path));
+        file.save()
+    }
+
+    fn eval(path: PathBuf, input: &str, pad: bool) -> Result<()> {
+        let file = Self::load_file(path)?;
+
+        let input: FpgaIO = input.parse().map_err(anyhow::Error::msg)?;
+        let output = match (pad, file.compiled()) {
+            (true, _) => file.fpga.eval_padded(&input.get_value_vec(), true)?,
+            (false, Some(compiled)) => compiled.eval(input)?,
+            (false, None) => file.fpga.eval(input)?,
+        };
+
+        println!("{output}");
+
+        Ok(())
+    }
+
+    fn show(path: PathBuf, select: Option<Position>) -> Result<()> {
+        let file = Self::load_file(path)?;
+
+        for line in export::ascii(&file.fpga, select.map(|Position(row, col)| (row, col))) {
+            println!("{line}");
+        }
+        for ((row, col), label) in file.labels() {
+            println!("({row}, {col}): {label}");
+        }
+
+        Ok(())
+    }
+
+    fn svg(path: PathBuf, output: PathBuf) -> Result<()> {
+        let fpga = Self::load(path)?;
+
+        fs::write(&output, export::svg(&fpga, &ViewerTheme::default()))
+            .with_context(|| format!("Failed to write {}", output.display()))
+    }
+
+    fn truth_table(path: PathBuf) -> Result<()> {
+        let file = Self::load_file(path)?;
+        let fpga = &file.fpga;
+
+        let inputs = fpga.width() * 2;
+        if inputs > Self::MAX_TRUTH_TABLE_INPUTS {
+            bail!(
+                "The board has {inputs} input bits, truth tables are limited to {}",
+                Self::MAX_TRUTH_TABLE_INPUTS
+            );
+        }
+
+        // Every input is evaluated, so the tables are worth
+        // computing when the file doesn't embed them.
+        let compiled = match file.compiled() {
+            Some(compiled) => compiled.clone(),
+            None => fpga.compile(),
+        };
+
+        let header: Vec<String> = (0..inputs)
+            .map(|i| format!("in_{i}"))
+            .chain((0..inputs).map(|i| format!("out_{i}")))
+            .collect();
+        println!("{}", header.join(","));
+
+        for value in 0..1usize << inputs {
+            // The first input bit is the most significant one,
+            // so that the rows are sorted like binary numbers.
+            let bits: Box<[bool]> = (0..inputs)
+                .map(|i| (value >> (inputs - 1 - i)) & 1 == 1)
+                .collect();
+
+            let output = compiled.eval(FpgaIO::from(bits.clone()))?;
+
+            let row: Vec<&str> = bits
+                .iter()
+                .copied()
+                .chain(output.iter_bits())
+                .map(|bit| if bit { "1" } else { "0" })
+                .collect();
+            println!("{}", row.join(","));
+        }
+
+        Ok(())
+    }
+
+    fn cell_truth_table(path: PathBuf, row: usize, col: usize) -> Result<()> {
+        let fpga = Self::load(path)?;
+        let cell = fpga
+            .get_cell(row, col)
+            .with_context(|| format!("Cell ({row}, {col}) is outside the FPGA"))?;
+
+        let table = cell.truth_table();
+        let lines = [
+            CellIO::COLUMN_1,
+            CellIO::COLUMN_2,
+            CellIO::ROW_1,
+            CellIO::ROW_2,
+        ];
+
+        println!("C1,C2,R1,R2,C1out,C2out,R1out,R2out");
+        for value in 0..16 {
+            // Sorted like the board truth table, with the
+            // first line as the most significant bit.
+            let input = CellIO::new(
+                value & 0b1000 != 0,
+                value & 0b0100 != 0,
+                value & 0b0010 != 0,
+                value & 0b0001 != 0,
+            );
+            let output = table[input.bits() as usize];
+
+            let row: Vec<String> = lines
+                .iter()
+                .map(|line| input.contains_as_u8(*line))
+                .chain(lines.iter().map(|line| output.contains_as_u8(*line)))
+                .map(|bit| bit.to_string())
+                .collect();
+            println!("{}", row.join(","));
+        }
+
+        Ok(())
+    }
+
+    fn stats(path: PathBuf) -> Result<()> {
+        let fpga = Self::load(path)?;
+        let distinct: HashSet<&Cell> = fpga.cells().collect();
+
+        let (width, height) = fpga.dimensions();
+        println!("{:<16}{width}x{height}", "Dimensions");
+        println!("{:<16}{}", "Total cells", width * height);
+        println!("{:<16}{}", "Used cells", fpga.used_cell_count());
+        println!("{:<16}{}", "Configurations", distinct.len());
+
+        println!();
+        println!("Outputs");
+        for (name, flag) in CellFlags::OUTPUTS.iter_names() {
+            let count = fpga
+                .cells()
+                .filter(|cell| cell.flags.contains(flag))
+                .count();
+            println!("  {name:<14}{count}");
+        }
+
+        let mut orders: HashMap<ActivationOrder, usize> = HashMap::new();
+        for cell in fpga.cells() {
+            *orders.entry(cell.activation_order).or_default() += 1;
+        }
+        let mut orders: Vec<(String, usize)> = orders
+            .into_iter()
+            .map(|(order, count)| {
+                let order: Vec<String> = order.into_iter().map(|sel| sel.to_string()).collect();
+                (order.join(" "), count)
+            })
+            .collect();
+        // The most common orders first.
+        orders.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+
+        println!();
+        println!("Activation orders");
+        for (order, count) in orders {
+            println!("  {order:<14}{count}");
+        }
+
+        Ok(())
+    }
+
+    fn search(path: PathBuf, flag: &str) -> Result<()> {
+        let flag = CellFlags::from_editable_name(flag).map_err(anyhow::Error::msg)?;
+        let fpga = Self::load(path)?;
+
+        let mut found = 0;
+        for (i, cell) in fpga.cells().enumerate() {
+            if cell.flags.contains(flag) {
+                println!("({}, {})", i / fpga.width(), i % fpga.width());
+                found += 1;
+            }
+        }
+        println!("{found} of {} cells", fpga.width() * fpga.height());
+
+        Ok(())
+    }
+
+    fn diff(old: PathBuf, new: PathBuf) -> Result<()> {
+        let old = Self::load(old)?;
+        let new = Self::load(new)?;
+
+        let Ok(cells) = diff(&old, &new) else {
+            println!(
+                "Dimensions differ: {}x{} -> {}x{}",
+                old.width(),
+                old.height(),
+                new.width(),
+                new.height()
+            );
+            return Ok(());
+        };
+
+        let parts = [
+            (CellDiff::ACTIVATION_ORDER, "activation order"),
+            (CellDiff::FLAGS, "flags"),
+            (CellDiff::FILLS, "fills"),
+        ];
+
+        for (row, col, cell_diff) in &cells {
+            let changed: Vec<&str> = parts
+                .iter()
+                .filter(|(part, _)| cell_diff.contains(*part))
+                .map(|(_, name)| *name)
+                .collect();
+            println!("({row}, {col}): {}", changed.join(", "));
+        }
+
+        println!(
+            "{} of {} cells differ ({} activation orders, {} flags, {} fills)",
+            cells.len(),
+            old.width() * old.height(),
+            Self::count(&cells, CellDiff::ACTIVATION_ORDER),
+            Self::count(&cells, CellDiff::FLAGS),
+            Self::count(&cells, CellDiff::FILLS),
+        );
+
+        Ok(())
+    }
+
+    fn count(cells: &[(usize, usize, CellDiff)], part: CellDiff) -> usize {
+        cells
+            .iter()
+            .filter(|(_, _, cell_diff)| cell_diff.contains(part))
+            .count()
+    }
+
+    fn set(path: PathBuf, row: usize, col: usize, flag: &str, state: Switch) -> Result<()> {
+        let flag = CellFlags::from_editable_name(flag).map_err(anyhow::Error::msg)?;
+
+        let cell = Self::edit_cell(path, row, col, |cell| {
+            cell.flags.set(flag, matches!(state, Switch::On));
+        })?;
+
+        for (name, flag) in CellFlags::EDITABLE.iter_names() {
+            let state = if cell.flags.contains(flag) {
+                "on"
+            } else {
+                "off"
+            };
+            println!("{name:<8} {state}");
+        }
+
+        if let Err(problems) = cell.flags.check_consistency() {
+            for problem in problems {
+                eprintln!("Warning: {problem}");
+            }
+        }
+
+        Ok(())
+    }
+
+    fn order(path: PathBuf, row: usize, col: usize, order: &[Selector]) -> Result<()> {
+        let order: [Selector; 4] = order
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Expected 4 lines, found {}", order.len()))?;
+        let order = ActivationOrder::new(order).map_err(anyhow::Error::msg)?;
+
+        Self::edit_cell(path, row, col, |cell| cell.activation_order = order)?;
+
+        let order: Vec<String> = order.into_iter().map(|sel| sel.to_string()).collect();
+        println!("{}", order.join(" -> "));
+
+        Ok(())
+    }
+
+    fn compile(path: PathBuf, strip: bool) -> Result<()> {
+        let mut file = Self::load_file(path)?;
+        file.embed_tables = !strip;
+        file.save()
+    }
+
+    fn label(path: PathBuf, row: usize, col: usize, text: Option<String>) -> Result<()> {
+        let mut file = Self::load_file(path)?;
+        file.set_label(row, col, text)?;
+        file.save()
+    }
+
+    /// Applies `edit` to a cell of the file and saves it,
+    /// returning the edited cell.
+    fn edit_cell(
+        path: PathBuf,
+        row: usize,
+        col: usize,
+        edit: impl FnOnce(&mut Cell),
+    ) -> Result<Cell> {
+        let mut file = Self::load_file(path)?;
+        let cell = file
+            .fpga
+            .get_mut(row, col)
+            .with_context(|| format!("Cell ({row}, {col}) is outside the FPGA"))?;
+
+        edit(cell);
+        let cell = *cell;
+
+        file.save()?;
+
+        Ok(cell)
+    }
+
+    /// Applies `edit` to the FPGA of the file and saves it.
+    fn edit(path: PathBuf, edit: impl FnOnce(&mut FPGA) -> Result<(), &'static str>) -> Result<()> {
+        let mut file = Self::load_file(path)?;
+        edit(&mut file.fpga).map_err(anyhow::Error::msg)?;
+        file.save()
+    }
 }
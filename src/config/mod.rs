@@ -0,0 +1,154 @@
+use crate::io::File;
+use crate::theme::ViewerTheme;
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::fs;
+use std::io::ErrorKind;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The user preferences read at startup from a TOML file,
+/// every key falling back to its default when missing.
+///
+/// ```toml
+/// width = 16
+/// height = 8
+/// theme = "High contrast"
+/// tick_rate = 30
+/// frame_rate = 60
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    /// The width prefilled when creating a new FPGA.
+    pub(crate) width: usize,
+    /// The height prefilled when creating a new FPGA.
+    pub(crate) height: usize,
+    /// The name of the [ViewerTheme] the viewer starts with.
+    theme: String,
+    /// How many times per second a running evaluation
+    /// reports its progress.
+    pub(crate) tick_rate: u32,
+    /// How many times per second the viewer scrolls
+    /// while the board is dragged.
+    pub(crate) frame_rate: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            width: 10,
+            height: 10,
+            theme: ViewerTheme::default().to_string(),
+            tick_rate: 30,
+            frame_rate: 60,
+        }
+    }
+}
+
+impl Config {
+    /// The directory inside the user's configuration directory.
+    const DIR: &'static str = "gb-fpga-simulator";
+    const FILE: &'static str = "config.toml";
+    /// The accepted tick and frame rates, per second, higher
+    /// ones would make their intervals too short to throttle.
+    const RATES: RangeInclusive<u32> = 1..=1000;
+
+    /// Where the configuration is read from, if the platform
+    /// has a configuration directory.
+    pub(crate) fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(Self::DIR).join(Self::FILE))
+    }
+
+    /// Reads the configuration from [Config::path].
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok(Config)] with the defaults if there is no file.
+    /// - [Err()] if the file can't be read or isn't valid.
+    pub(crate) fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                Self::parse(&content).with_context(|| format!("Invalid {}", path.display()))
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Parses and validates the TOML `content` of a configuration file.
+    pub(crate) fn parse(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content)?;
+
+        File::validate_dimensions(config.width, config.height).map_err(anyhow::Error::msg)?;
+        if config.viewer_theme().is_none() {
+            bail!("Unknown theme `{}`", config.theme);
+        }
+        if !Self::RATES.contains(&config.tick_rate) {
+            bail!("The tick rate must be between 1 and {}", Self::RATES.end());
+        }
+        if !Self::RATES.contains(&config.frame_rate) {
+            bail!("The frame rate must be between 1 and {}", Self::RATES.end());
+        }
+
+        Ok(config)
+    }
+
+    fn viewer_theme(&self) -> Option<ViewerTheme> {
+        ViewerTheme::ALL
+            .into_iter()
+            .find(|theme| theme.to_string().eq_ignore_ascii_case(&self.theme))
+    }
+
+    /// The theme the viewer starts with.
+    pub(crate) fn theme(&self) -> ViewerTheme {
+        self.viewer_theme().unwrap_or_default()
+    }
+
+    /// The time between two progress reports of an evaluation.
+    pub(crate) fn tick_interval(&self) -> Duration {
+        Self::interval(self.tick_rate)
+    }
+
+    /// The time between two scrolls of the viewer while it's dragged.
+    pub(crate) fn frame_interval(&self) -> Duration {
+        Self::interval(self.frame_rate)
+    }
+
+    /// The time between two events happening `rate` times per second.
+    fn interval(rate: u32) -> Duration {
+        Duration::from_secs(1) / rate.clamp(*Self::RATES.start(), *Self::RATES.end())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_use_the_defaults() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+
+        let config = Config::parse("width = 16\ntheme = \"light\"").unwrap();
+        assert_eq!((config.width, config.height), (16, 10));
+        assert_eq!(config.theme(), ViewerTheme::LIGHT);
+        assert_eq!(config.tick_interval(), Duration::from_secs(1) / 30);
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        assert!(Config::parse("width = 0").is_err());
+        assert!(Config::parse("theme = \"Neon\"").is_err());
+        assert!(Config::parse("tick_rate = 0").is_err());
+        assert!(Config::parse("tick_rate = 1000").is_ok());
+        assert!(Config::parse("tick_rate = 1001").is_err());
+        assert!(Config::parse("frame_rate = 4000000000").is_err());
+        assert!(Config::parse("frame_rate = -1").is_err());
+        assert!(Config::parse("size = 4").is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use crate::theme::ViewerTheme;
+use iced::Color;
+use simulator_core::FPGA;
+use simulator_core::cell::{Cell, CellFlags};
+use std::fmt::Write;
+
+/// The size, in SVG units, of a single cell pixel.
+const PIXEL_SIZE: usize = 10;
+/// The amount of pixels on each side of a cell.
+const CELL_PIXELS: usize = 8;
+
+/// Renders `fpga` as an SVG image, laid out like the
+/// [FpgaViewer](crate::gui::fpga_viewer::FpgaViewer) does and colored with `theme`.
+///
+/// Every pixel is a `<rect>`, transparent pixels are left
+/// out and the activation order is drawn as `<text>`.
+pub(crate) fn svg(fpga: &FPGA, theme: &ViewerTheme) -> String {
+    let cell_size = PIXEL_SIZE * CELL_PIXELS;
+    let width = fpga.width() * cell_size;
+    let height = fpga.height() * cell_size;
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    );
+    svg.push('\n');
+
+    // Like in the viewer the last row is on top, and the
+    // directions alternate starting from it.
+    for (y, row) in (0..fpga.height()).rev().enumerate() {
+        let direction = y % 2 == 0;
+        for col in 0..fpga.width() {
+            let cell = fpga.get_cell(row, col).expect("Cell inside the FPGA");
+            cell_svg(
+                &mut svg,
+                theme,
+                cell,
+                col * cell_size,
+                y * cell_size,
+                direction,
+            );
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Appends the pixels of `cell`, whose top left corner is at `(x, y)`.
+fn cell_svg(
+    svg: &mut String,
+    theme: &ViewerTheme,
+    cell: &Cell,
+    x: usize,
+    y: usize,
+    direction: bool,
+) {
+    let flags = &cell.flags;
+
+    let when = |flag, on: Color, off: Color| {
+        if flags.contains(flag) { on } else { off }
+    };
+    let out = |flag| when(flag, theme.out, Color::TRANSPARENT);
+    let not = |flag| when(flag, theme.not, theme.normal);
+    let junction = |flag| when(flag, theme.junction, theme.normal);
+    let normal = theme.normal;
+
+    let mut pixel = |px: usize, py: usize, color: Color| {
+        let color = ViewerTheme::cell_color(color, flags);
+        if color.a == 0.0 {
+            return;
+        }
+
+        let [r, g, b, a] = color.into_rgba8();
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{PIXEL_SIZE}" height="{PIXEL_SIZE}" fill="rgb({r},{g},{b})" fill-opacity="{:.2}"/>"#,
+            x + px * PIXEL_SIZE,
+            y + py * PIXEL_SIZE,
+            a as f32 / 255.0,
+        );
+    };
+
+    pixel(2, 0, out(CellFlags::C2_OUT));
+    pixel(5, 0, out(CellFlags::C1_OUT));
+    for py in [1, 3, 4, 6] {
+        pixel(2, py, not(CellFlags::NOT_C2));
+        pixel(5, py, not(CellFlags::NOT_C1));
+    }
+
+    let lines = [
+        (2, CellFlags::R2_OUT, CellFlags::JC2_R2, CellFlags::JC1_R2),
+        (5, CellFlags::R1_OUT, CellFlags::JC2_R1, CellFlags::JC1_R1),
+    ];
+    let (out_x, order_x) = if direction { (0, 7) } else { (7, 0) };
+    for (py, line_out, jc2, jc1) in lines {
+        pixel(out_x, py, out(line_out));
+        pixel(order_x, py, normal);
+        for px in [1, 3, 4, 6] {
+            pixel(px, py, normal);
+        }
+        pixel(2, py, junction(jc2));
+        pixel(5, py, junction(jc1));
+    }
+
+    pixel(2, 7, normal);
+    pixel(5, 7, normal);
+
+    // The order pixels, indexed like the selectors.
+    let order_pixels = [(5, 7), (2, 7), (order_x, 5), (order_x, 2)];
+    for (i, selector) in cell.activation_order.into_iter().enumerate() {
+        let (px, py) = order_pixels[selector as usize];
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" font-size="{}" text-anchor="middle" dominant-baseline="central" fill="white">{i}</text>"#,
+            x + px * PIXEL_SIZE + PIXEL_SIZE / 2,
+            y + py * PIXEL_SIZE + PIXEL_SIZE / 2,
+            PIXEL_SIZE as f32 * 0.92,
+        );
+    }
+}
+
+/// The amount of characters on each side of a cell, in
+/// [ascii] renders, including the selection markers.
+const ASCII_WIDTH: usize = 7;
+const ASCII_HEIGHT: usize = 4;
+
+/// Renders `fpga` as text, one [String] per line, laid out like
+/// the [FpgaViewer](crate::gui::fpga_viewer::FpgaViewer) does with the `selected` `(row, col)` cell
+/// between brackets.
+///
+/// Columns are drawn with `|`, or `X` when negated, rows with
+/// `-`, junctions with `+` and enabled outputs with `o`.
+pub(crate) fn ascii(fpga: &FPGA, selected: Option<(usize, usize)>) -> Vec<String> {
+    let mut lines = Vec::with_capacity(fpga.height() * ASCII_HEIGHT);
+
+    for (y, row) in (0..fpga.height()).rev().enumerate() {
+        let direction = y % 2 == 0;
+        let mut cell_lines: [String; ASCII_HEIGHT] = Default::default();
+
+        for col in 0..fpga.width() {
+            let cell = fpga.get_cell(row, col).expect("Cell inside the FPGA");
+            let (open, close) = match selected == Some((row, col)) {
+                true => ('[', ']'),
+                false => (' ', ' '),
+            };
+
+            for (line, chars) in cell_lines.iter_mut().zip(cell_ascii(cell, direction)) {
+                line.push(open);
+                line.extend(chars);
+                line.push(close);
+            }
+        }
+
+        lines.extend(cell_lines);
+    }
+
+    lines
+}
+
+/// Draws the characters of `cell`, without the selection markers.
+fn cell_ascii(cell: &Cell, direction: bool) -> [[char; ASCII_WIDTH - 2]; ASCII_HEIGHT] {
+    let flags = &cell.flags;
+    let when = |flag, on: char, off: char| if flags.contains(flag) { on } else { off };
+
+    let c1 = when(CellFlags::NOT_C1, 'X', '|');
+    let c2 = when(CellFlags::NOT_C2, 'X', '|');
+
+    let line = |out, jc2, jc1| {
+        let out = when(out, 'o', '-');
+        let (left, right) = if direction { (out, '-') } else { ('-', out) };
+        [left, when(jc2, '+', c2), '-', when(jc1, '+', c1), right]
+    };
+
+    [
+        [
+            ' ',
+            when(CellFlags::C2_OUT, 'o', ' '),
+            ' ',
+            when(CellFlags::C1_OUT, 'o', ' '),
+            ' ',
+        ],
+        line(CellFlags::R2_OUT, CellFlags::JC2_R2, CellFlags::JC1_R2),
+        line(CellFlags::R1_OUT, CellFlags::JC2_R1, CellFlags::JC1_R1),
+        [' ', c2, ' ', c1, ' '],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn svg_pixels() {
+        // Every cell has 24 visible pixels, plus one
+        // for each of its enabled outputs.
+        let mut fpga = FPGA::new(3, 2);
+        let svg = svg(&fpga, &ViewerTheme::default());
+        assert_eq!(svg.matches("<rect").count(), 6 * 24);
+        assert_eq!(svg.matches("<text").count(), 6 * 4);
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+
+        fpga.get_mut(1, 2)
+            .unwrap()
+            .flags
+            .insert(CellFlags::C1_OUT | CellFlags::R2_OUT);
+        assert_eq!(
+            super::svg(&fpga, &ViewerTheme::default())
+                .matches("<rect")
+                .count(),
+            6 * 24 + 2
+        );
+    }
+
+    #[test]
+    fn ascii_cells() {
+        let mut fpga = FPGA::new(2, 2);
+        let cell = fpga.get_mut(0, 1).unwrap();
+        cell.flags
+            .insert(CellFlags::NOT_C1 | CellFlags::JC2_R1 | CellFlags::C1_OUT | CellFlags::R1_OUT);
+
+        let lines = ascii(&fpga, Some((0, 1)));
+        assert_eq!(lines.len(), 8);
+        assert!(lines.iter().all(|line| line.chars().count() == 14));
+
+        // The first row is at the bottom, with its outputs on the right.
+        assert_eq!(lines[1], " -|-|-  -|-|- ");
+        assert_eq!(lines[4], "       [   o ]");
+        assert_eq!(lines[5], " -|-|- [-|-X-]");
+        assert_eq!(lines[6], " -|-|- [-+-Xo]");
+        assert_eq!(lines[7], "  | |  [ | X ]");
+    }
+}
@@ -0,0 +1,112 @@
+use crate::gui::Message;
+use iced::futures::SinkExt;
+use iced::futures::channel::oneshot;
+use iced::task::Handle;
+use iced::widget::{button, progress_bar, row, text, text_input};
+use iced::{Element, Length, Task};
+use simulator_core::{EvalError, FPGA, FpgaIO};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// An [FPGA] evaluation running in the background.
+#[derive(Debug)]
+pub(crate) struct Evaluation {
+    /// The share of rows evaluated so far, from 0 to 1.
+    pub(crate) progress: f32,
+    cancel: Arc<AtomicBool>,
+    handle: Handle,
+}
+
+impl Evaluation {
+    /// Starts evaluating a copy of `fpga` on a dedicated thread,
+    /// reporting through [Message::EvalProgress], at most once
+    /// every `tick_interval`, and [Message::EvalDone].
+    pub(crate) fn start(
+        fpga: FPGA,
+        input: FpgaIO,
+        tick_interval: Duration,
+    ) -> (Self, Task<Message>) {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = cancel.clone();
+
+        let stream = iced::stream::channel(16, move |mut output| async move {
+            let (result_tx, result_rx) = oneshot::channel();
+            let mut progress = output.clone();
+
+            thread::spawn(move || {
+                let height = fpga.height().max(1) as f32;
+                let mut last_tick: Option<Instant> = None;
+                let result = fpga.eval_with_progress(input, |rows| {
+                    if last_tick.is_none_or(|tick| tick.elapsed() >= tick_interval) {
+                        last_tick = Some(Instant::now());
+                        // Progress updates are dropped while the UI is busy,
+                        // only the final result has to be delivered.
+                        let _ = progress.try_send(Message::EvalProgress(rows as f32 / height));
+                    }
+
+                    if worker_cancel.load(Ordering::Relaxed) {
+                        ControlFlow::Break(())
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                });
+                let _ = result_tx.send(result);
+            });
+
+            let result = result_rx.await.unwrap_or(Err(EvalError::Cancelled));
+            let _ = output.send(Message::EvalDone(result)).await;
+        });
+
+        let (task, handle) = Task::stream(stream).abortable();
+
+        (
+            Self {
+                progress: 0.0,
+                cancel,
+                handle,
+            },
+            task,
+        )
+    }
+
+    /// Stops the evaluation, the worker thread exits at the
+    /// end of the row it is evaluating.
+    pub(crate) fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.handle.abort();
+    }
+}
+
+/// The bar at the bottom of the window used to start
+/// evaluations and follow their progress.
+pub(crate) fn status_bar<'a>(
+    input: &'a str,
+    evaluation: Option<&'a Evaluation>,
+    status: &'a str,
+) -> Element<'a, Message> {
+    let input = text_input("Input bits, e.g. 0110", input)
+        .on_input(Message::EvalInput)
+        .on_submit_maybe(evaluation.is_none().then_some(Message::Eval))
+        .width(Length::Fixed(250.0));
+
+    match evaluation {
+        Some(evaluation) => row![
+            input,
+            button("Cancel").on_press(Message::EvalCancel),
+            progress_bar(0.0..=1.0, evaluation.progress).width(Length::Fixed(200.0)),
+            text(format!("{:.0}%", evaluation.progress * 100.0)),
+        ],
+        None => row![
+            input,
+            button("Evaluate").on_press(Message::Eval),
+            text(status),
+        ],
+    }
+    .spacing(10)
+    .padding(5)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
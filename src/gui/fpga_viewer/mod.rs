@@ -1,36 +1,201 @@
+use crate::config::Config;
 use crate::gui::Message;
 use crate::io::File;
-use iced::widget::{Column, Container, Row, Space, container, text};
-use iced::{Background, Color, Length, Renderer, Theme};
+use crate::theme::ViewerTheme;
+use iced::widget::scrollable::{self, AbsoluteOffset, RelativeOffset, Viewport};
+use iced::widget::{Column, Container, Row, Space, container, mouse_area, stack, text, tooltip};
+use iced::{
+    Background, Border, Color, Element, Fill, Length, Point, Renderer, Task, Theme, Vector,
+};
 use iced_aw::{Grid, GridRow};
-use simulator_core::cell::{ActivationOrder, CellFlags};
+use simulator_core::cell::{ActivationOrder, CellFlags, CellIO, Selector};
+use std::collections::HashSet;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// The `(row, col)` of a set of cells.
+pub(crate) type Selection = HashSet<(usize, usize)>;
 
 #[derive(Debug)]
 pub(crate) struct FpgaViewer {
     pub(crate) file_resource: Arc<RwLock<File>>,
+    pub(crate) theme: ViewerTheme,
+    /// The region of the viewer which is visible, known once
+    /// it has been scrolled for the first time.
+    pub(crate) viewport: Option<Viewport>,
+    /// The last cursor position over the viewer.
+    cursor: Point,
+    /// Where the board was last dragged from, while it's panned.
+    pan: Option<Point>,
+    /// The least time between two scrolls while the board is panned.
+    frame_interval: Duration,
+    /// When the board was last scrolled by panning it.
+    last_scroll: Option<Instant>,
+    /// The `(row, col)` of the last cell clicked.
+    pub(crate) selected: Option<(usize, usize)>,
+    /// The `(row, col)` of every selected cell, highlighted on the board.
+    pub(crate) selection: Selection,
+    /// The cell a selection box is dragged from, with the
+    /// selection it was started from, while it's dragged.
+    drag: Option<((usize, usize), Selection)>,
+    /// Whether the row and column indices are shown around the board.
+    pub(crate) rulers: bool,
+    /// Whether a faint line is drawn around each cell.
+    pub(crate) grid_lines: bool,
     pixel_size: f32,
 }
 impl FpgaViewer {
-    const NOT_COLOR: Color = Color::from_rgb(0.45, 0.0, 0.0);
-    const NORMAL_COLOR: Color = Color::from_rgb(0.29, 0.29, 0.32);
-    const JUNCTION_COLOR: Color = Color::from_rgb(0.05, 0.9, 0.8);
-    const OUT_COLOR: Color = Color::from_rgb(0.82, 0.05, 0.88);
+    /// The opacity of the grid lines.
+    const GRID_LINE_ALPHA: f32 = 0.35;
 
-    pub fn new(file_resource: Arc<RwLock<File>>) -> Self {
+    pub fn new(file_resource: Arc<RwLock<File>>, config: &Config) -> Self {
         Self {
             file_resource,
+            theme: config.theme(),
+            viewport: None,
+            cursor: Point::ORIGIN,
+            pan: None,
+            frame_interval: config.frame_interval(),
+            last_scroll: None,
+            selected: None,
+            selection: Selection::new(),
+            drag: None,
+            rulers: false,
+            grid_lines: false,
             pixel_size: 10f32,
         }
     }
 
+    /// The [scrollable] the viewer is shown in.
+    pub(crate) fn scrollable_id() -> scrollable::Id {
+        scrollable::Id::new("fpga_viewer")
+    }
+
+    /// Whether the board is being dragged around.
+    pub(crate) fn is_panning(&self) -> bool {
+        self.pan.is_some()
+    }
+
+    /// Starts dragging the board from the cursor position.
+    pub(crate) fn start_pan(&mut self) {
+        self.pan = Some(self.cursor);
+    }
+
+    /// Stops panning, scrolling by the moves held back since the
+    /// last scroll so that the board follows the cursor to the end.
+    pub(crate) fn end_pan<T>(&mut self) -> Task<T> {
+        self.last_scroll = None;
+        match self.pan.take() {
+            Some(last) if last != self.cursor => self.scroll_by(last - self.cursor),
+            _ => Task::none(),
+        }
+    }
+
+    /// Follows the cursor, moved to `position`, scrolling the
+    /// viewer by the same amount while the board is dragged.
+    ///
+    /// The viewer scrolls at most once per frame, the moves in
+    /// between are added up into the next scroll.
+    pub(crate) fn move_cursor<T>(&mut self, position: Point) -> Task<T> {
+        self.cursor = position;
+        let Some(last) = self.pan else {
+            return Task::none();
+        };
+        let now = Instant::now();
+        if self
+            .last_scroll
+            .is_some_and(|scroll| now - scroll < self.frame_interval)
+        {
+            return Task::none();
+        }
+        self.pan = Some(position);
+        self.last_scroll = Some(now);
+
+        self.scroll_by(last - position)
+    }
+
+    fn scroll_by<T>(&self, delta: Vector) -> Task<T> {
+        scrollable::scroll_by(
+            Self::scrollable_id(),
+            AbsoluteOffset {
+                x: delta.x,
+                y: delta.y,
+            },
+        )
+    }
+
+    /// Starts a selection box on the `(row, col)` cell, adding
+    /// to the current selection if `extend`, or replacing it.
+    pub(crate) fn start_selection(&mut self, row: usize, col: usize, extend: bool) {
+        if !extend {
+            self.selection.clear();
+        }
+        self.selected = Some((row, col));
+        self.selection.insert((row, col));
+        self.drag = Some(((row, col), self.selection.clone()));
+    }
+
+    /// Stretches the selection box, if one is dragged, so that
+    /// `(row, col)` is its opposite corner.
+    pub(crate) fn drag_selection(&mut self, row: usize, col: usize) {
+        let Some(((start_row, start_col), base)) = &self.drag else {
+            return;
+        };
+
+        let rows = (*start_row).min(row)..=(*start_row).max(row);
+        let cols = (*start_col).min(col)..=(*start_col).max(col);
+        self.selection = base.clone();
+        self.selection
+            .extend(rows.flat_map(|row| cols.clone().map(move |col| (row, col))));
+    }
+
+    pub(crate) fn end_selection(&mut self) {
+        self.drag = None;
+    }
+
+    /// Deselects every cell, as when the board is replaced.
+    pub(crate) fn clear_selection(&mut self) {
+        self.selected = None;
+        self.selection.clear();
+        self.drag = None;
+    }
+
+    /// Scrolls the viewer so that the `(row, col)` cell is
+    /// in the middle of it, as far as possible.
+    pub(crate) fn scroll_to_cell<T>(&self, row: usize, col: usize) -> Task<T> {
+        let file = self.file_resource.read().unwrap();
+        let (width, height) = file.fpga.dimensions();
+
+        // The share of the content that comes before the
+        // center of the cell, remembering that the last
+        // row is on top.
+        let x = (col as f32 + 0.5) / width.max(1) as f32;
+        let y = (height.saturating_sub(row) as f32 - 0.5) / height.max(1) as f32;
+
+        match self.viewport {
+            Some(viewport) => {
+                let content = viewport.content_bounds();
+                let bounds = viewport.bounds();
+                let offset = AbsoluteOffset {
+                    x: (x * content.width - bounds.width / 2.0).max(0.0),
+                    y: (y * content.height - bounds.height / 2.0).max(0.0),
+                };
+                scrollable::scroll_to(Self::scrollable_id(), offset)
+            }
+            None => scrollable::snap_to(Self::scrollable_id(), RelativeOffset { x, y }),
+        }
+    }
+
+    /// Draws the board, in the signal view if `signals` holds
+    /// the output of each cell in the order returned by
+    /// [FPGA::eval_trace](simulator_core::FPGA::eval_trace).
     #[inline]
-    pub(crate) fn view(&self) -> Grid<'_, Message, Theme, Renderer> {
+    pub(crate) fn view(&self, signals: Option<&[CellIO]>) -> Grid<'_, Message, Theme, Renderer> {
         let mut grid = Grid::new();
 
         let file = self.file_resource.read().unwrap();
 
-        if file.fpga.height() == 0 || file.fpga.width() == 1 {
+        if file.fpga.is_empty() {
             return grid;
         }
 
@@ -38,16 +203,87 @@ impl FpgaViewer {
 
         for row in (0..file.fpga.height()).rev() {
             let mut grid_row: GridRow<'_, Message, Theme, Renderer> = GridRow::new();
+            if self.rulers {
+                grid_row =
+                    grid_row.push(self.ruler(row, self.pixel_size * 3.0, self.pixel_size * 8.0));
+            }
             for col in 0..file.fpga.width() {
-                grid_row = grid_row.push(self.cell(row, col, direction));
+                let signal = signals
+                    .and_then(|signals| signals.get(row * file.fpga.width() + col))
+                    .copied()
+                    .unwrap_or(CellIO::empty());
+                let mut cell: Element<'_, Message> = self.cell(row, col, direction, signal).into();
+                if self.grid_lines {
+                    let color = Color {
+                        a: Self::GRID_LINE_ALPHA,
+                        ..self.theme.normal
+                    };
+                    cell =
+                        stack![cell, self.border(color, (self.pixel_size / 10.0).max(1.0))].into();
+                }
+                if self.selection.contains(&(row, col)) {
+                    cell = stack![cell, self.border(self.theme.signal, 2.0)].into();
+                }
+                let cell = mouse_area(cell)
+                    .on_press(Message::CellClicked(row, col))
+                    .on_enter(Message::CellHovered(row, col));
+                grid_row = match file.label(row, col) {
+                    Some(label) => grid_row.push(
+                        tooltip(
+                            cell,
+                            text(label.to_owned()),
+                            tooltip::Position::FollowCursor,
+                        )
+                        .style(container::rounded_box)
+                        .padding(5),
+                    ),
+                    None => grid_row.push(cell),
+                };
             }
             direction = !direction;
             grid = grid.push(grid_row)
         }
 
+        // The column indices go below the first row.
+        if self.rulers {
+            let mut grid_row: GridRow<'_, Message, Theme, Renderer> =
+                GridRow::new().push(Space::new(0, 0));
+            for col in 0..file.fpga.width() {
+                grid_row =
+                    grid_row.push(self.ruler(col, self.pixel_size * 8.0, self.pixel_size * 3.0));
+            }
+            grid = grid.push(grid_row);
+        }
+
         grid
     }
 
+    /// The label of a row or column index, centered in
+    /// a `width` by `height` box.
+    fn ruler(
+        &self,
+        index: usize,
+        width: f32,
+        height: f32,
+    ) -> Container<'_, Message, Theme, Renderer> {
+        container(text(index).size(self.pixel_size * 1.2))
+            .center_x(Length::Fixed(width))
+            .center_y(Length::Fixed(height))
+    }
+
+    /// A `width` wide border drawn above a cell, for
+    /// the grid lines and the selected cells.
+    fn border(&self, color: Color, width: f32) -> Container<'_, Message, Theme, Renderer> {
+        container(Space::new(Fill, Fill)).style(move |_| container::Style {
+            border: Border {
+                color,
+                width,
+                radius: 0.0.into(),
+            },
+            ..container::Style::default()
+        })
+    }
+
     #[inline]
     pub(crate) fn get_path(&self) -> String {
         let file = self.file_resource.read().unwrap();
@@ -57,12 +293,15 @@ impl FpgaViewer {
         )
     }
 
+    /// Draws the `(row, col)` cell, with the lines carrying
+    /// `signal` lit up.
     #[inline]
     pub(crate) fn cell(
         &self,
         row: usize,
         col: usize,
         direction: bool,
+        signal: CellIO,
     ) -> Column<'_, Message, Theme, Renderer> {
         let file = self.file_resource.read().unwrap();
 
@@ -76,18 +315,25 @@ impl FpgaViewer {
 
         let empty = || self.pixel(Color::TRANSPARENT);
 
-        let row_1 = || self.pixel(Self::NORMAL_COLOR);
-        let row_2 = || self.pixel(Self::NORMAL_COLOR);
+        let line = |cell_io| {
+            if signal.contains(cell_io) {
+                self.theme.signal
+            } else {
+                self.theme.normal
+            }
+        };
 
-        let col_1 = || self.not_pixel(CellFlags::NOT_C1, flags);
-        let col_2 = || self.not_pixel(CellFlags::NOT_C2, flags);
+        let row_1 = || self.cell_pixel(line(CellIO::ROW_1), flags);
+        let row_2 = || self.cell_pixel(line(CellIO::ROW_2), flags);
 
-        let junction = |cell_flag| self.junction_pixel(cell_flag, flags);
+        let col_1 = || self.not_pixel(CellFlags::NOT_C1, flags, line(CellIO::COLUMN_1));
+        let col_2 = || self.not_pixel(CellFlags::NOT_C2, flags, line(CellIO::COLUMN_2));
 
-        let jc1_r1 = junction(CellFlags::JC1_R1);
-        let jc1_r2 = junction(CellFlags::JC1_R2);
-        let jc2_r1 = junction(CellFlags::JC2_R1);
-        let jc2_r2 = junction(CellFlags::JC2_R2);
+        // Where there's no junction the pixel belongs to the row.
+        let jc1_r1 = self.junction_pixel(CellFlags::JC1_R1, flags, line(CellIO::ROW_1));
+        let jc1_r2 = self.junction_pixel(CellFlags::JC1_R2, flags, line(CellIO::ROW_2));
+        let jc2_r1 = self.junction_pixel(CellFlags::JC2_R1, flags, line(CellIO::ROW_1));
+        let jc2_r2 = self.junction_pixel(CellFlags::JC2_R2, flags, line(CellIO::ROW_2));
 
         let out = |cell_flag| self.out_pixel(cell_flag, flags);
 
@@ -97,7 +343,7 @@ impl FpgaViewer {
         let col_2_out = out(CellFlags::C2_OUT);
 
         let [col_1_order, col_2_order, row_1_order, row_2_order] =
-            self.order_pixels(&cell_data.activation_order);
+            self.order_pixels(&cell_data.activation_order, flags);
 
         let mut row = Row::new().spacing(0);
 
@@ -179,7 +425,7 @@ impl FpgaViewer {
 
         if direction {
             row = row.push(row_1_out);
-            row = row.push(row_2());
+            row = row.push(row_1());
             row = row.push(jc2_r1);
             row = row.push(row_1());
             row = row.push(row_1());
@@ -188,7 +434,7 @@ impl FpgaViewer {
             row = row.push(row_1_order);
         } else {
             row = row.push(row_1_order);
-            row = row.push(row_2());
+            row = row.push(row_1());
             row = row.push(jc2_r1);
             row = row.push(row_1());
             row = row.push(row_1());
@@ -231,28 +477,33 @@ impl FpgaViewer {
     fn order_pixels(
         &self,
         activation_order: &ActivationOrder,
+        cell_flags: &CellFlags,
     ) -> [Container<'_, Message, Theme, Renderer>; 4] {
-        let mut vec: [Container<Message, Theme, Renderer>; 4] =
-            std::array::from_fn(|_| self.pixel(Color::TRANSPARENT));
+        let background = ViewerTheme::cell_color(self.theme.normal, cell_flags);
 
-        for (i, selector) in activation_order.into_iter().enumerate() {
-            let txt = text(i)
+        let order_pixel = |selector| {
+            let txt = text(activation_order.position_of(selector))
                 .size(self.pixel_size * 0.92)
                 .align_x(iced::Alignment::Center)
                 .align_y(iced::Alignment::Center);
 
-            vec[selector as usize] = container(txt)
+            container(txt)
                 .width(Length::Fixed(self.pixel_size))
                 .height(Length::Fixed(self.pixel_size))
                 .align_x(iced::Alignment::Center)
                 .align_y(iced::Alignment::Center)
-                .style(|_| container::Style {
-                    background: Some(Background::Color(FpgaViewer::NORMAL_COLOR)),
+                .style(move |_| container::Style {
+                    background: Some(Background::Color(background)),
                     ..Default::default()
-                });
-        }
+                })
+        };
 
-        vec
+        [
+            order_pixel(Selector::Column1),
+            order_pixel(Selector::Column2),
+            order_pixel(Selector::Row1),
+            order_pixel(Selector::Row2),
+        ]
     }
 
     #[inline]
@@ -262,11 +513,11 @@ impl FpgaViewer {
         cell_flags: &CellFlags,
     ) -> Container<'_, Message, Theme, Renderer> {
         let tmp = if cell_flags.contains(out) {
-            FpgaViewer::OUT_COLOR
+            self.theme.out
         } else {
             Color::TRANSPARENT
         };
-        self.pixel(tmp)
+        self.cell_pixel(tmp, cell_flags)
     }
 
     #[inline]
@@ -274,13 +525,14 @@ impl FpgaViewer {
         &self,
         not: CellFlags,
         cell_flags: &CellFlags,
+        line: Color,
     ) -> Container<'_, Message, Theme, Renderer> {
         let tmp = if cell_flags.contains(not) {
-            FpgaViewer::NOT_COLOR
+            self.theme.not
         } else {
-            FpgaViewer::NORMAL_COLOR
+            line
         };
-        self.pixel(tmp)
+        self.cell_pixel(tmp, cell_flags)
     }
 
     #[inline]
@@ -288,13 +540,23 @@ impl FpgaViewer {
         &self,
         junction: CellFlags,
         cell_flags: &CellFlags,
+        line: Color,
     ) -> Container<'_, Message, Theme, Renderer> {
         let tmp = if cell_flags.contains(junction) {
-            FpgaViewer::JUNCTION_COLOR
+            self.theme.junction
         } else {
-            FpgaViewer::NORMAL_COLOR
+            line
         };
-        self.pixel(tmp)
+        self.cell_pixel(tmp, cell_flags)
+    }
+
+    #[inline]
+    fn cell_pixel(
+        &self,
+        color: Color,
+        cell_flags: &CellFlags,
+    ) -> Container<'_, Message, Theme, Renderer> {
+        self.pixel(ViewerTheme::cell_color(color, cell_flags))
     }
 
     #[inline]
@@ -309,3 +571,59 @@ impl FpgaViewer {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pan_throttling() {
+        let mut config = Config::default();
+        config.frame_rate = 1;
+        let mut viewer = FpgaViewer::new(Arc::default(), &config);
+
+        let _ = viewer.move_cursor::<Message>(Point::ORIGIN);
+        viewer.start_pan();
+        let _ = viewer.move_cursor::<Message>(Point::new(5.0, 5.0));
+        assert_eq!(viewer.pan, Some(Point::new(5.0, 5.0)));
+
+        // Held back until the next frame, or until the pan ends.
+        let _ = viewer.move_cursor::<Message>(Point::new(8.0, 9.0));
+        assert_eq!(viewer.pan, Some(Point::new(5.0, 5.0)));
+        assert_eq!(viewer.cursor, Point::new(8.0, 9.0));
+
+        let _ = viewer.end_pan::<Message>();
+        assert!(!viewer.is_panning());
+        assert_eq!(viewer.last_scroll, None);
+    }
+
+    #[test]
+    fn box_selection() {
+        let mut viewer = FpgaViewer::new(Arc::default(), &Config::default());
+
+        viewer.start_selection(1, 2, false);
+        viewer.drag_selection(3, 4);
+        viewer.drag_selection(2, 1);
+        viewer.end_selection();
+        assert_eq!(
+            viewer.selection,
+            HashSet::from([(1, 1), (1, 2), (2, 1), (2, 2)])
+        );
+
+        // Dragging after the box was released changes nothing.
+        viewer.drag_selection(0, 0);
+        assert_eq!(viewer.selection.len(), 4);
+
+        viewer.start_selection(5, 5, true);
+        viewer.drag_selection(5, 6);
+        assert_eq!(viewer.selection.len(), 6);
+        assert_eq!(viewer.selected, Some((5, 5)));
+
+        viewer.start_selection(0, 0, false);
+        assert_eq!(viewer.selection, HashSet::from([(0, 0)]));
+
+        viewer.clear_selection();
+        assert!(viewer.selection.is_empty());
+        assert_eq!(viewer.selected, None);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::gui::Message;
+use crate::theme::ViewerTheme;
+use iced::widget::scrollable::Viewport;
+use iced::widget::{Column, Row, Space, container, mouse_area, stack};
+use iced::{Background, Border, Color, Element, Length};
+use simulator_core::FPGA;
+use simulator_core::cell::CellFlags;
+
+/// The size, in pixels, of the longest side of the minimap.
+const SIZE: f32 = 150.0;
+
+/// An overview of the whole [FPGA] with a pixel for each cell, laid
+/// out like the [FpgaViewer](crate::gui::fpga_viewer::FpgaViewer) does, and a rectangle around the region
+/// that's visible in `viewport`. Clicking a cell emits
+/// [Message::MinimapJump] with its `(row, col)`.
+pub(crate) fn view<'a>(
+    fpga: &FPGA,
+    theme: &ViewerTheme,
+    viewport: Option<Viewport>,
+) -> Element<'a, Message> {
+    let (width, height) = fpga.dimensions();
+    let pixel = (SIZE / width.max(height).max(1) as f32).clamp(1.0, 8.0);
+
+    let mut cells = Column::new();
+    for row in (0..height).rev() {
+        let mut cells_row = Row::new();
+        for col in 0..width {
+            let flags = fpga.get_cell(row, col).expect("Cell inside the FPGA").flags;
+            let color = ViewerTheme::cell_color(dominant_color(&flags, theme), &flags);
+
+            cells_row = cells_row.push(
+                mouse_area(
+                    container(Space::new(Length::Fixed(pixel), Length::Fixed(pixel))).style(
+                        move |_| container::Style {
+                            background: Some(Background::Color(color)),
+                            ..container::Style::default()
+                        },
+                    ),
+                )
+                .on_press(Message::MinimapJump(row, col)),
+            );
+        }
+        cells = cells.push(cells_row);
+    }
+
+    let Some(viewport) = viewport else {
+        return cells.into();
+    };
+
+    // The visible region, scaled from the content of the
+    // viewer to the size of the minimap.
+    let content = viewport.content_bounds();
+    let offset = viewport.absolute_offset();
+    let bounds = viewport.bounds();
+    let scale_x = width as f32 * pixel / content.width.max(1.0);
+    let scale_y = height as f32 * pixel / content.height.max(1.0);
+
+    let region = Column::new()
+        .push(Space::with_height(offset.y * scale_y))
+        .push(
+            Row::new().push(Space::with_width(offset.x * scale_x)).push(
+                container(Space::new(
+                    (bounds.width * scale_x).min(width as f32 * pixel),
+                    (bounds.height * scale_y).min(height as f32 * pixel),
+                ))
+                .style(|_| container::Style {
+                    border: Border {
+                        color: Color::WHITE,
+                        width: 1.0,
+                        radius: 0.0.into(),
+                    },
+                    ..container::Style::default()
+                }),
+            ),
+        );
+
+    stack![cells, region].into()
+}
+
+/// The color of the most relevant flags of a cell: its outputs,
+/// then its NOTs and then its junctions.
+fn dominant_color(flags: &CellFlags, theme: &ViewerTheme) -> Color {
+    if !flags.outputs().is_empty() {
+        theme.out
+    } else if !flags.nots().is_empty() {
+        theme.not
+    } else if !flags.junctions().is_empty() {
+        theme.junction
+    } else {
+        theme.normal
+    }
+}
@@ -1,40 +1,146 @@
+use crate::config::Config;
+use crate::gui::evaluation::Evaluation;
 use crate::gui::fpga_viewer::FpgaViewer;
+use crate::gui::new_file_modal::NewFileModal;
+use crate::gui::palette::Palette;
+use crate::gui::simulation::Simulation;
 use crate::io::File;
-use iced::widget::{column, container};
-use iced::{Element, Fill, Shrink, Size, Task};
+use crate::theme::ViewerTheme;
+use iced::keyboard::key::Named;
+use iced::keyboard::{Key, Modifiers};
+use iced::widget::{
+    button, center, checkbox, column, container, mouse_area, opaque, pick_list, row, scrollable,
+    stack, text,
+};
+use iced::{
+    Color, Element, Event, Fill, Length, Point, Size, Subscription, Task, event, keyboard, mouse,
+    window,
+};
+use iced_aw::menu::{Item, Menu, MenuBar};
+use simulator_core::cell::{Cell, CellFlags};
+use simulator_core::{EvalError, FpgaIO};
 use std::string::ToString;
 use std::sync::{Arc, RwLock};
 
+pub(crate) mod evaluation;
 pub(crate) mod fpga_viewer;
+pub(crate) mod minimap;
+pub(crate) mod new_file_modal;
+pub(crate) mod palette;
+pub(crate) mod simulation;
 
 #[derive(Debug, Clone)]
-pub enum Message {}
+pub enum Message {
+    NewFile,
+    Open,
+    Save,
+    #[cfg(feature = "clipboard")]
+    Copy,
+    #[cfg(feature = "clipboard")]
+    Paste,
+    #[cfg(feature = "clipboard")]
+    Pasted(Option<String>),
+    ModalWidth(String),
+    ModalHeight(String),
+    ModalConfirm,
+    ModalCancel,
+    EvalInput(String),
+    Eval,
+    EvalProgress(f32),
+    EvalDone(Result<FpgaIO, EvalError>),
+    EvalCancel,
+    SimulationToggle,
+    SimulationInput(usize, bool),
+    SignalView(bool),
+    CloseRequested(window::Id),
+    QuitConfirm,
+    ViewerTheme(ViewerTheme),
+    ViewerScrolled(scrollable::Viewport),
+    PanStart,
+    PanMove(Point),
+    PanEnd,
+    /// The cursor left the viewer.
+    ViewerExit,
+    MinimapJump(usize, usize),
+    ViewRulers(bool),
+    ViewGridLines(bool),
+    CellClicked(usize, usize),
+    CellHovered(usize, usize),
+    SelectionEnd,
+    ModifiersChanged(Modifiers),
+    PaletteToggle,
+    PaletteSelect(usize),
+    PaletteName(String),
+    PaletteSave,
+    BulkFlag(&'static str),
+    /// Turns the chosen flag on, or off, in every selected cell.
+    BulkSetFlag(bool),
+    /// Copies the last cell clicked to every selected cell.
+    BulkApplyCell,
+}
 
 pub struct GUI {
     title: String,
+    /// The preferences read at startup.
+    config: Config,
     fpga_viewer: FpgaViewer,
+    new_file_modal: Option<NewFileModal>,
+    eval_input: String,
+    evaluation: Option<Evaluation>,
+    /// The simulation panel, while it's open.
+    simulation: Option<Simulation>,
+    palette: Palette,
+    /// Whether the palette panel is shown.
+    palette_open: bool,
+    status: String,
+    /// Whether the FPGA has changes that weren't saved yet.
+    is_dirty: bool,
+    /// The window waiting for the user to confirm closing it
+    /// while there are unsaved changes.
+    close_request: Option<window::Id>,
+    /// The error shown to the user until dismissed.
+    error: Option<String>,
+    /// The modifier keys held down, Shift adding to the selection.
+    modifiers: Modifiers,
+    /// The flag the selection toolbar sets or clears.
+    bulk_flag: &'static str,
 }
 
 impl GUI {
     const TITLE: &'static str = "Ghost Block FPGA Simulator";
 
-    pub fn new(file_resource: Arc<RwLock<File>>) -> (Self, Task<Message>) {
+    pub fn new(file_resource: Arc<RwLock<File>>, config: Config) -> (Self, Task<Message>) {
         (
             Self {
                 title: GUI::TITLE.to_string(),
-                fpga_viewer: FpgaViewer::new(file_resource),
+                fpga_viewer: FpgaViewer::new(file_resource, &config),
+                config,
+                new_file_modal: None,
+                eval_input: String::new(),
+                evaluation: None,
+                simulation: None,
+                palette: Palette::default(),
+                palette_open: false,
+                status: String::new(),
+                is_dirty: false,
+                close_request: None,
+                error: None,
+                modifiers: Modifiers::empty(),
+                bulk_flag: "JC1_R1",
             },
             Task::none(),
         )
     }
 
-    pub fn run(file_resource: Arc<RwLock<File>>) -> iced::Result {
+    pub fn run(file_resource: Arc<RwLock<File>>, config: Config) -> iced::Result {
         iced::application(GUI::title, GUI::update, GUI::view)
             .theme(GUI::theme)
             .window_size(Size::new(1000.0, 600.0))
             .centered()
             .antialiasing(true)
-            .run_with(|| GUI::new(file_resource))
+            .subscription(GUI::subscription)
+            .exit_on_close_request(false)
+            .run_with(|| GUI::new(file_resource, config))
     }
 
     pub fn title(&self) -> String {
@@ -44,27 +150,488 @@ impl GUI {
     }
 
     pub fn theme(&self) -> iced::Theme {
-        iced::Theme::Dark
+        self.fpga_viewer.theme.app_theme()
+    }
+
+    pub(crate) fn subscription(&self) -> Subscription<Message> {
+        Subscription::batch([
+            window::close_requests().map(Message::CloseRequested),
+            keyboard::on_key_press(GUI::shortcut),
+            event::listen_with(|event, _, _| match event {
+                Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                    Some(Message::ModifiersChanged(modifiers))
+                }
+                _ => None,
+            }),
+        ])
+    }
+
+    /// Maps the keyboard shortcuts to their [Message].
+    fn shortcut(key: Key, modifiers: Modifiers) -> Option<Message> {
+        match key.as_ref() {
+            Key::Named(Named::Escape) => Some(Message::ModalCancel),
+            Key::Character(c) if modifiers.command() => match c {
+                "n" => Some(Message::NewFile),
+                "o" => Some(Message::Open),
+                "s" => Some(Message::Save),
+                #[cfg(feature = "clipboard")]
+                "c" => Some(Message::Copy),
+                #[cfg(feature = "clipboard")]
+                "v" => Some(Message::Paste),
+                _ => None,
+            },
+            _ => None,
+        }
     }
 
     pub(crate) fn update(&mut self, message: Message) -> Task<Message> {
-        match message {}
+        match message {
+            Message::NewFile => {
+                self.new_file_modal = Some(NewFileModal::new(self.config.width, self.config.height))
+            }
+            Message::ModalWidth(width) => {
+                if let Some(modal) = &mut self.new_file_modal {
+                    modal.width = width;
+                }
+            }
+            Message::ModalHeight(height) => {
+                if let Some(modal) = &mut self.new_file_modal {
+                    modal.height = height;
+                }
+            }
+            Message::ModalConfirm => {
+                // The modal only allows confirming valid dimensions.
+                if let Some(Ok((width, height))) =
+                    self.new_file_modal.as_ref().map(NewFileModal::dimensions)
+                {
+                    let mut file = self.fpga_viewer.file_resource.write().unwrap();
+                    file.new_fpga(width, height);
+                    drop(file);
+                    self.new_file_modal = None;
+                    self.fpga_viewer.clear_selection();
+                    self.is_dirty = true;
+                    self.run_simulation();
+                }
+            }
+            Message::Open => {
+                let opened = self
+                    .fpga_viewer
+                    .file_resource
+                    .write()
+                    .unwrap()
+                    .open_file_dialog();
+                match opened {
+                    Ok(true) => {
+                        self.fpga_viewer.clear_selection();
+                        self.is_dirty = false;
+                        self.run_simulation();
+                    }
+                    Ok(false) => {}
+                    Err(err) => self.error = Some(format!("Failed to open the file: {err:#}")),
+                }
+            }
+            Message::Save => {
+                let mut file = self.fpga_viewer.file_resource.write().unwrap();
+                let saved = match file.get_path() {
+                    Some(_) => file.save().map(|()| true),
+                    None => file.save_as(),
+                };
+                match saved {
+                    Ok(true) => self.is_dirty = false,
+                    Ok(false) => {}
+                    Err(err) => self.error = Some(format!("Failed to save the file: {err:#}")),
+                }
+            }
+            #[cfg(feature = "clipboard")]
+            Message::Copy => {
+                let file = self.fpga_viewer.file_resource.read().unwrap();
+                return iced::clipboard::write(crate::io::clipboard::encode(&file.fpga));
+            }
+            #[cfg(feature = "clipboard")]
+            Message::Paste => return iced::clipboard::read().map(Message::Pasted),
+            #[cfg(feature = "clipboard")]
+            Message::Pasted(content) => {
+                match crate::io::clipboard::decode(content.as_deref().unwrap_or_default()) {
+                    Ok(fpga) => {
+                        self.fpga_viewer
+                            .file_resource
+                            .write()
+                            .unwrap()
+                            .replace_fpga(fpga);
+                        self.fpga_viewer.clear_selection();
+                        self.is_dirty = true;
+                        self.run_simulation();
+                    }
+                    Err(err) => self.error = Some(format!("Failed to paste the FPGA: {err:#}")),
+                }
+            }
+            Message::ModalCancel => {
+                self.new_file_modal = None;
+                self.close_request = None;
+                self.error = None;
+            }
+            Message::EvalInput(input) => self.eval_input = input,
+            Message::Eval => {
+                let input = match self.eval_input.trim().parse::<FpgaIO>() {
+                    Ok(input) => input,
+                    Err(err) => {
+                        self.status = err.to_string();
+                        return Task::none();
+                    }
+                };
+
+                let fpga = self.fpga_viewer.file_resource.read().unwrap().fpga.clone();
+                let (evaluation, task) =
+                    Evaluation::start(fpga, input, self.config.tick_interval());
+                self.evaluation = Some(evaluation);
+
+                return task;
+            }
+            Message::EvalProgress(progress) => {
+                if let Some(evaluation) = &mut self.evaluation {
+                    evaluation.progress = progress;
+                }
+            }
+            Message::EvalDone(result) => {
+                self.evaluation = None;
+                self.status = match result {
+                    Ok(output) => format!("Output: {output}"),
+                    Err(err) => err.to_string(),
+                };
+            }
+            Message::EvalCancel => {
+                if let Some(evaluation) = self.evaluation.take() {
+                    evaluation.cancel();
+                    self.status = EvalError::Cancelled.to_string();
+                }
+            }
+            Message::SimulationToggle => {
+                self.simulation = match self.simulation {
+                    Some(_) => None,
+                    None => Some(Simulation::default()),
+                };
+                self.run_simulation();
+            }
+            Message::SimulationInput(bit, value) => {
+                if let Some(simulation) = &mut self.simulation {
+                    let file = self.fpga_viewer.file_resource.read().unwrap();
+                    simulation.set_input(&file.fpga, bit, value);
+                }
+            }
+            Message::SignalView(signal_view) => {
+                if let Some(simulation) = &mut self.simulation {
+                    simulation.signal_view = signal_view;
+                }
+            }
+            Message::CloseRequested(id) => {
+                if !self.is_dirty {
+                    return window::close(id);
+                }
+                self.close_request = Some(id);
+            }
+            Message::QuitConfirm => {
+                if let Some(id) = self.close_request.take() {
+                    return window::close(id);
+                }
+            }
+            Message::ViewerTheme(theme) => self.fpga_viewer.theme = theme,
+            Message::ViewerScrolled(viewport) => self.fpga_viewer.viewport = Some(viewport),
+            Message::MinimapJump(row, col) => return self.fpga_viewer.scroll_to_cell(row, col),
+            Message::ViewRulers(rulers) => self.fpga_viewer.rulers = rulers,
+            Message::ViewGridLines(grid_lines) => self.fpga_viewer.grid_lines = grid_lines,
+            Message::PanStart => self.fpga_viewer.start_pan(),
+            Message::PanMove(position) => return self.fpga_viewer.move_cursor(position),
+            Message::PanEnd => return self.fpga_viewer.end_pan(),
+            Message::ViewerExit => {
+                self.fpga_viewer.end_selection();
+                return self.fpga_viewer.end_pan();
+            }
+            Message::CellHovered(row, col) => self.fpga_viewer.drag_selection(row, col),
+            Message::SelectionEnd => self.fpga_viewer.end_selection(),
+            Message::ModifiersChanged(modifiers) => self.modifiers = modifiers,
+            Message::CellClicked(row, col) => {
+                self.fpga_viewer
+                    .start_selection(row, col, self.modifiers.shift());
+                // The palette only stamps its cells while it's shown.
+                let Some(cell) = self.palette.selected().filter(|_| self.palette_open) else {
+                    return Task::none();
+                };
+
+                let mut file = self.fpga_viewer.file_resource.write().unwrap();
+                if file.set_cell(row, col, *cell).is_ok() {
+                    drop(file);
+                    self.is_dirty = true;
+                    self.run_simulation();
+                }
+            }
+            Message::PaletteToggle => self.palette_open = !self.palette_open,
+            Message::PaletteSelect(index) => self.palette.select(index),
+            Message::PaletteName(name) => self.palette.name = name,
+            Message::PaletteSave => {
+                if let Some(cell) = self.selected_cell() {
+                    self.palette.save(cell);
+                }
+            }
+            Message::BulkFlag(flag) => self.bulk_flag = flag,
+            Message::BulkSetFlag(state) => {
+                if let Ok(flag) = CellFlags::from_editable_name(self.bulk_flag) {
+                    self.edit_selection(|cell| cell.flags.set(flag, state));
+                }
+            }
+            Message::BulkApplyCell => {
+                if let Some(source) = self.selected_cell() {
+                    self.edit_selection(|cell| *cell = source);
+                }
+            }
+        }
+
+        Task::none()
+    }
+
+    /// Evaluates the board again in the simulation panel, if it's open.
+    fn run_simulation(&mut self) {
+        if let Some(simulation) = &mut self.simulation {
+            let file = self.fpga_viewer.file_resource.read().unwrap();
+            simulation.run(&file.fpga);
+        }
+    }
+
+    /// Edits every selected cell with `f`, see
+    /// [FPGA::apply_to_region](simulator_core::FPGA::apply_to_region).
+    fn edit_selection(&mut self, f: impl FnMut(&mut Cell)) {
+        let mut file = self.fpga_viewer.file_resource.write().unwrap();
+        match file.apply_to_region(self.fpga_viewer.selection.iter().copied(), f) {
+            Ok(()) => {
+                drop(file);
+                self.is_dirty = true;
+                self.run_simulation();
+            }
+            Err(err) => self.error = Some(format!("Failed to edit the selection: {err:#}")),
+        }
+    }
+
+    /// The bar editing every selected cell at once, shown while
+    /// some are selected.
+    fn selection_bar(&self) -> Option<Element<'_, Message>> {
+        let count = self.fpga_viewer.selection.len();
+        if count == 0 {
+            return None;
+        }
+
+        let flags: Vec<&'static str> = CellFlags::EDITABLE
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect();
+        Some(
+            row![
+                text(format!("{count} selected")),
+                pick_list(flags, Some(self.bulk_flag), Message::BulkFlag),
+                button("Set on all").on_press(Message::BulkSetFlag(true)),
+                button("Clear on all").on_press(Message::BulkSetFlag(false)),
+                button("Apply cell to all")
+                    .on_press_maybe(self.selected_cell().map(|_| Message::BulkApplyCell)),
+            ]
+            .spacing(10)
+            .padding(5)
+            .align_y(iced::Alignment::Center)
+            .into(),
+        )
+    }
+
+    /// The configuration of the last cell clicked, if it's
+    /// still on the board.
+    fn selected_cell(&self) -> Option<Cell> {
+        let (row, col) = self.fpga_viewer.selected?;
+        let file = self.fpga_viewer.file_resource.read().unwrap();
+        file.fpga.get_cell(row, col).copied()
+    }
+
+    /// The menu toggling the overlays of the viewer.
+    fn view_menu(&self) -> Element<'_, Message> {
+        let menu = Menu::new(vec![
+            Item::new(checkbox("Rulers", self.fpga_viewer.rulers).on_toggle(Message::ViewRulers)),
+            Item::new(
+                checkbox("Grid lines", self.fpga_viewer.grid_lines)
+                    .on_toggle(Message::ViewGridLines),
+            ),
+        ])
+        .max_width(150.0)
+        .spacing(5.0);
+
+        MenuBar::new(vec![Item::with_menu(
+            container(text("View")).padding([5, 10]),
+            menu,
+        )])
+        .into()
     }
 
     pub(crate) fn view(&self) -> Element<'_, Message> {
+        let toolbar = row![
+            button("New").on_press(Message::NewFile),
+            button("Open").on_press(Message::Open),
+            button("Save").on_press(Message::Save),
+        ];
+        #[cfg(feature = "clipboard")]
+        let toolbar = toolbar
+            .push(button("Copy").on_press(Message::Copy))
+            .push(button("Paste").on_press(Message::Paste));
+        let toolbar = toolbar
+            .push(pick_list(
+                ViewerTheme::ALL,
+                Some(self.fpga_viewer.theme),
+                Message::ViewerTheme,
+            ))
+            .push(button("Simulate").on_press(Message::SimulationToggle))
+            .push(button("Palette").on_press(Message::PaletteToggle))
+            .push(self.view_menu())
+            .spacing(10)
+            .padding(5);
+
+        let signals = self.simulation.as_ref().and_then(Simulation::signals);
+        let viewer = scrollable(self.fpga_viewer.view(signals))
+            .id(FpgaViewer::scrollable_id())
+            .direction(scrollable::Direction::Both {
+                vertical: scrollable::Scrollbar::default(),
+                horizontal: scrollable::Scrollbar::default(),
+            })
+            .on_scroll(Message::ViewerScrolled);
+        // The board is dragged around with the middle button, while
+        // the left one drags a selection box from the cell pressed.
+        let viewer = mouse_area(viewer)
+            .on_release(Message::SelectionEnd)
+            .on_middle_press(Message::PanStart)
+            .on_middle_release(Message::PanEnd)
+            .on_exit(Message::ViewerExit)
+            .on_move(Message::PanMove)
+            .interaction(if self.fpga_viewer.is_panning() {
+                mouse::Interaction::Grabbing
+            } else {
+                mouse::Interaction::Idle
+            });
+
+        let mut board = row![];
+        if self.palette_open {
+            board = board.push(self.palette.view(self.selected_cell().is_some()));
+        }
+        board = board.push(container(viewer).center(Fill));
+        {
+            let file = self.fpga_viewer.file_resource.read().unwrap();
+            if !file.fpga.is_empty() {
+                board = board.push(
+                    container(minimap::view(
+                        &file.fpga,
+                        &self.fpga_viewer.theme,
+                        self.fpga_viewer.viewport,
+                    ))
+                    .padding(10),
+                );
+            }
+        }
+
+        let mut content = column![toolbar];
+        if let Some(selection_bar) = self.selection_bar() {
+            content = content.push(selection_bar);
+        }
+        content = content.push(board);
+        if let Some(simulation) = &self.simulation {
+            content = content.push(simulation.view());
+        }
         let main_content = container(
-            column![
-                container(self.fpga_viewer.view())
-                    .height(Shrink)
-                    .width(Shrink)
-                    .center(Fill)
-            ]
-            .width(Fill)
-            .height(Fill),
+            content
+                .push(evaluation::status_bar(
+                    &self.eval_input,
+                    self.evaluation.as_ref(),
+                    &self.status,
+                ))
+                .width(Fill)
+                .height(Fill),
         )
         .width(Fill)
         .height(Fill);
 
-        main_content.into()
+        if let Some(error) = &self.error {
+            return Self::modal(
+                main_content.into(),
+                Self::error_dialog(error),
+                Message::ModalCancel,
+            );
+        }
+
+        if self.close_request.is_some() {
+            return Self::modal(
+                main_content.into(),
+                Self::quit_confirmation(),
+                Message::ModalCancel,
+            );
+        }
+
+        match &self.new_file_modal {
+            Some(modal) => Self::modal(main_content.into(), modal.view(), Message::ModalCancel),
+            None => main_content.into(),
+        }
+    }
+
+    /// Shows `error` until it gets dismissed.
+    fn error_dialog(error: &str) -> Element<'_, Message> {
+        container(
+            column![
+                text("Error").size(20),
+                text(error),
+                button("Dismiss").on_press(Message::ModalCancel),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fixed(300.0))
+        .padding(20)
+        .style(container::rounded_box)
+        .into()
+    }
+
+    /// Asks whether to close the window, losing the unsaved changes.
+    fn quit_confirmation<'a>() -> Element<'a, Message> {
+        container(
+            column![
+                text("Unsaved changes").size(20),
+                text("The FPGA has changes that weren't saved, quit anyway?"),
+                row![
+                    button("Cancel").on_press(Message::ModalCancel),
+                    button("Quit").on_press(Message::QuitConfirm),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fixed(300.0))
+        .padding(20)
+        .style(container::rounded_box)
+        .into()
+    }
+
+    /// Shows `content` above `base`, on a backdrop that
+    /// emits `on_blur` when clicked.
+    fn modal<'a>(
+        base: Element<'a, Message>,
+        content: Element<'a, Message>,
+        on_blur: Message,
+    ) -> Element<'a, Message> {
+        stack![
+            base,
+            opaque(
+                mouse_area(center(opaque(content)).style(|_theme| {
+                    container::Style {
+                        background: Some(
+                            Color {
+                                a: 0.8,
+                                ..Color::BLACK
+                            }
+                            .into(),
+                        ),
+                        ..container::Style::default()
+                    }
+                }))
+                .on_press(on_blur)
+            )
+        ]
+        .into()
     }
 }
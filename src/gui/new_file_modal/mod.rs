@@ -0,0 +1,104 @@
+use crate::gui::Message;
+use crate::io::File;
+use iced::widget::{button, column, container, row, text, text_input};
+use iced::{Color, Element, Length};
+
+/// The state of the modal used to create a new, blank, FPGA.
+#[derive(Debug, Clone)]
+pub(crate) struct NewFileModal {
+    pub(crate) width: String,
+    pub(crate) height: String,
+}
+
+impl NewFileModal {
+    const ERROR_COLOR: Color = Color::from_rgb(0.9, 0.2, 0.2);
+
+    /// The modal prefilled with the given dimensions.
+    pub(crate) fn new(width: usize, height: usize) -> Self {
+        Self {
+            width: width.to_string(),
+            height: height.to_string(),
+        }
+    }
+
+    /// Validates the inserted dimensions.
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok((width, height))] if both are valid.
+    /// - [Err()] with the reason otherwise.
+    pub(crate) fn dimensions(&self) -> Result<(usize, usize), &'static str> {
+        let width = self
+            .width
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| "Width must be a number")?;
+        let height = self
+            .height
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| "Height must be a number")?;
+
+        File::validate_dimensions(width, height)?;
+
+        Ok((width, height))
+    }
+
+    pub(crate) fn view(&self) -> Element<'_, Message> {
+        let dimensions = self.dimensions();
+
+        let error = text(dimensions.err().unwrap_or_default())
+            .size(14)
+            .color(Self::ERROR_COLOR);
+
+        let confirm = dimensions.is_ok().then_some(Message::ModalConfirm);
+
+        container(
+            column![
+                text("New FPGA").size(20),
+                text_input("Width", &self.width)
+                    .on_input(Message::ModalWidth)
+                    .on_submit_maybe(confirm.clone()),
+                text_input("Height", &self.height)
+                    .on_input(Message::ModalHeight)
+                    .on_submit_maybe(confirm.clone()),
+                error,
+                row![
+                    button("Cancel").on_press(Message::ModalCancel),
+                    button("Create").on_press_maybe(confirm),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fixed(300.0))
+        .padding(20)
+        .style(container::rounded_box)
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modal(width: &str, height: &str) -> NewFileModal {
+        NewFileModal {
+            width: width.to_string(),
+            height: height.to_string(),
+        }
+    }
+
+    #[test]
+    fn dimensions_validation() {
+        assert_eq!(modal("10", "4").dimensions(), Ok((10, 4)));
+        assert_eq!(modal(" 2 ", "1").dimensions(), Ok((2, 1)));
+
+        assert!(modal("ten", "4").dimensions().is_err());
+        assert!(modal("10", "").dimensions().is_err());
+        assert!(modal("-3", "4").dimensions().is_err());
+        assert!(modal("0", "4").dimensions().is_err());
+        assert_eq!(modal("1", "4").dimensions(), Ok((1, 4)));
+        assert!(modal("10", "0").dimensions().is_err());
+    }
+}
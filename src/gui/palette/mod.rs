@@ -0,0 +1,210 @@
+use crate::gui::Message;
+use iced::widget::{Column, button, column, row, scrollable, text, text_input};
+use iced::{Element, Length, Theme};
+use simulator_core::cell::Cell;
+use simulator_core::schematic::{ParseError, parse_schematic};
+
+/// The cells bundled with the simulator, in the format
+/// documented at the top of the file.
+const LIBRARY: &str = include_str!("library.schematic");
+
+/// Reads a library of cells: a schematic of a single row
+/// whose cells are named by the comment of their line.
+/// The cells without a name get one from their column.
+pub(crate) fn parse_library(src: &str) -> Result<Vec<(String, Cell)>, ParseError> {
+    let fpga = parse_schematic(src)?;
+    if fpga.height() > 1 {
+        return Err(ParseError {
+            line: 1,
+            column: 1,
+            message: "A library has a single row of cells",
+        });
+    }
+
+    let mut entries: Vec<(String, Cell)> = fpga
+        .cells()
+        .enumerate()
+        .map(|(col, cell)| (format!("Cell {col}"), *cell))
+        .collect();
+
+    for line in src.lines() {
+        let Some((item, name)) = line.split_once('#') else {
+            continue;
+        };
+        let item = item.trim();
+        // The schematic was parsed, so the position is valid.
+        let col = item
+            .get(..4)
+            .filter(|keyword| keyword.eq_ignore_ascii_case("cell"))
+            .and_then(|_| item[4..].split_once(':'))
+            .and_then(|(position, _)| position.split_once(','))
+            .and_then(|(_, col)| col.trim().parse::<usize>().ok());
+        let name = name.trim();
+
+        if let Some(entry) = col.and_then(|col| entries.get_mut(col))
+            && !name.is_empty()
+        {
+            entry.0 = name.to_owned();
+        }
+    }
+
+    Ok(entries)
+}
+
+/// A list of named cells to stamp onto the board, starting
+/// with the bundled ones.
+#[derive(Debug)]
+pub(crate) struct Palette {
+    entries: Vec<(String, Cell)>,
+    selected: Option<usize>,
+    /// The name of the next cell saved to the palette.
+    pub(crate) name: String,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            entries: parse_library(LIBRARY).expect("The bundled library is valid"),
+            selected: None,
+            name: String::new(),
+        }
+    }
+}
+
+impl Palette {
+    /// Selects the `index` entry, or clears the
+    /// selection if it was already selected.
+    pub(crate) fn select(&mut self, index: usize) {
+        self.selected = match self.selected {
+            Some(selected) if selected == index => None,
+            _ => Some(index).filter(|&index| index < self.entries.len()),
+        };
+    }
+
+    /// The cell that clicking the board stamps, if any.
+    pub(crate) fn selected(&self) -> Option<&Cell> {
+        self.selected.map(|index| &self.entries[index].1)
+    }
+
+    /// Adds `cell` to the palette under the typed name,
+    /// doing nothing while it's empty.
+    pub(crate) fn save(&mut self, cell: Cell) {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return;
+        }
+
+        self.entries.push((name.to_owned(), cell));
+        self.name.clear();
+    }
+
+    /// The panel listing the entries, with the form saving the
+    /// current cell, enabled if `can_save`.
+    pub(crate) fn view(&self, can_save: bool) -> Element<'_, Message> {
+        let mut entries = Column::new().spacing(5);
+        for (index, (name, _)) in self.entries.iter().enumerate() {
+            let style: fn(&Theme, button::Status) -> button::Style = if self.selected == Some(index)
+            {
+                button::primary
+            } else {
+                button::secondary
+            };
+            entries = entries.push(
+                button(text(name))
+                    .width(Length::Fill)
+                    .style(style)
+                    .on_press(Message::PaletteSelect(index)),
+            );
+        }
+
+        let can_save = can_save && !self.name.trim().is_empty();
+        column![
+            text("Palette").size(20),
+            scrollable(entries).height(Length::Fill),
+            row![
+                text_input("Name", &self.name).on_input(Message::PaletteName),
+                button("Save cell").on_press_maybe(can_save.then_some(Message::PaletteSave)),
+            ]
+            .spacing(5),
+        ]
+        .spacing(10)
+        .padding(5)
+        .width(Length::Fixed(200.0))
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simulator_core::cell::CellIO;
+
+    /// The output of a gate given its two inputs.
+    type Gate = fn(bool, bool) -> bool;
+
+    #[test]
+    fn bundled_library() {
+        let palette = Palette::default();
+        let gates: [(&str, Gate); 4] = [
+            ("AND", |a, b| a && b),
+            ("OR", |a, b| a || b),
+            ("NOT", |_, b| !b),
+            ("BUFFER", |a, _| a),
+        ];
+
+        for (name, gate) in gates {
+            let (_, cell) = palette
+                .entries
+                .iter()
+                .find(|(entry, _)| entry == name)
+                .unwrap();
+            for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+                let output = cell.eval_cell(CellIO::new(a, b, false, false));
+                assert_eq!(output.contains(CellIO::COLUMN_1), gate(a, b), "{name}");
+            }
+        }
+
+        let (_, wire) = palette
+            .entries
+            .iter()
+            .find(|(entry, _)| entry == "Wire")
+            .unwrap();
+        for a in [false, true] {
+            let output = wire.eval_cell(CellIO::new(a, false, false, false));
+            assert_eq!(output.contains(CellIO::ROW_1), a);
+        }
+    }
+
+    #[test]
+    fn library_names() {
+        let entries = parse_library("size 2x1\ncell 0,1: not c1 # Negated\n").unwrap();
+        let names: Vec<_> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["Cell 0", "Negated"]);
+
+        assert!(parse_library("size 1x2").is_err());
+    }
+
+    #[test]
+    fn select_and_save() {
+        let mut palette = Palette::default();
+        let count = palette.entries.len();
+
+        palette.select(1);
+        assert_eq!(palette.selected(), Some(&palette.entries[1].1));
+        palette.select(1);
+        assert_eq!(palette.selected(), None);
+        palette.select(count);
+        assert_eq!(palette.selected(), None);
+
+        palette.save(Cell::default());
+        assert_eq!(palette.entries.len(), count);
+
+        palette.name = " Mine ".to_owned();
+        palette.save(Cell::default());
+        assert_eq!(
+            palette.entries.last(),
+            Some(&("Mine".to_owned(), Cell::default()))
+        );
+        assert!(palette.name.is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+use crate::gui::Message;
+use iced::widget::{Column, Row, column, container, scrollable, text, toggler};
+use iced::{Alignment, Element};
+use simulator_core::cell::CellIO;
+use simulator_core::{EvalError, FPGA, FpgaIO};
+
+/// The height of each row of the panel, so that the
+/// labels line up with the bits.
+const ROW_HEIGHT: f32 = 20.0;
+
+/// The interactive evaluation of the [FPGA]: a toggle for each of
+/// its input bits, evaluating it again whenever one is flipped.
+#[derive(Debug, Default)]
+pub(crate) struct Simulation {
+    inputs: Vec<bool>,
+    output: Option<Result<(FpgaIO, Vec<CellIO>), EvalError>>,
+    /// Whether the viewer shows the signals instead of the
+    /// configuration of the board.
+    pub(crate) signal_view: bool,
+}
+
+impl Simulation {
+    /// Evaluates `fpga` with the current inputs, resizing them
+    /// first if the board changed since the last evaluation.
+    pub(crate) fn run(&mut self, fpga: &FPGA) {
+        self.inputs.resize(fpga.width() * 2, false);
+        let input = FpgaIO::from(self.inputs.clone().into_boxed_slice());
+        self.output = Some(fpga.eval_trace(input));
+    }
+
+    /// Sets the input `bit` to `value` and evaluates `fpga` again.
+    pub(crate) fn set_input(&mut self, fpga: &FPGA, bit: usize, value: bool) {
+        if let Some(input) = self.inputs.get_mut(bit) {
+            *input = value;
+        }
+        self.run(fpga);
+    }
+
+    /// The output of each cell, for the viewer to draw while
+    /// in the signal view.
+    pub(crate) fn signals(&self) -> Option<&[CellIO]> {
+        match &self.output {
+            Some(Ok((_, trace))) if self.signal_view => Some(trace),
+            _ => None,
+        }
+    }
+
+    /// The panel with the input toggles, each one above the
+    /// read-only output bit of the same column.
+    pub(crate) fn view(&self) -> Element<'_, Message> {
+        let outputs = match &self.output {
+            Some(Ok((output, _))) => output.get_value_vec(),
+            _ => Box::default(),
+        };
+
+        let mut bits = Row::new().spacing(5);
+        for (bit, input) in self.inputs.iter().enumerate() {
+            let output = outputs.get(bit).copied().unwrap_or_default();
+            bits = bits.push(
+                column![
+                    text(bit).size(12).height(ROW_HEIGHT),
+                    container(
+                        toggler(*input)
+                            .on_toggle(move |value| Message::SimulationInput(bit, value))
+                    )
+                    .center_y(ROW_HEIGHT),
+                    // Without `on_toggle` the output can't be flipped.
+                    container(toggler(output)).center_y(ROW_HEIGHT),
+                ]
+                .spacing(5)
+                .align_x(Alignment::Center),
+            );
+        }
+
+        let mut panel = Column::new()
+            .push(
+                toggler(self.signal_view)
+                    .label("Signal view")
+                    .on_toggle(Message::SignalView),
+            )
+            .push(
+                Row::new()
+                    .push(
+                        column![
+                            text("Bit").size(12).height(ROW_HEIGHT),
+                            text("Input").height(ROW_HEIGHT),
+                            text("Output").height(ROW_HEIGHT),
+                        ]
+                        .spacing(5)
+                        .padding([0, 5]),
+                    )
+                    .push(
+                        scrollable(bits).direction(scrollable::Direction::Horizontal(
+                            scrollable::Scrollbar::default(),
+                        )),
+                    ),
+            );
+        if let Some(Err(err)) = &self.output {
+            panel = panel.push(text(err.to_string()));
+        }
+
+        panel.spacing(5).padding(5).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inputs_follow_the_board() {
+        let fpga = FPGA::new(3, 2);
+        let mut simulation = Simulation::default();
+
+        simulation.run(&fpga);
+        assert_eq!(simulation.inputs, [false; 6]);
+
+        simulation.set_input(&fpga, 2, true);
+        simulation.set_input(&fpga, 6, true);
+        assert_eq!(simulation.inputs, [false, false, true, false, false, false]);
+        assert_eq!(
+            simulation.output,
+            Some(fpga.eval_trace("001000".parse().unwrap()))
+        );
+        assert_eq!(simulation.signals(), None);
+
+        simulation.signal_view = true;
+        assert_eq!(simulation.signals().map(<[_]>::len), Some(6));
+
+        simulation.run(&FPGA::new(2, 2));
+        assert_eq!(simulation.inputs, [false, false, true, false]);
+    }
+}
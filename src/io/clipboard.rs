@@ -0,0 +1,59 @@
+//! Converts an [FPGA] from and to the text that gets copied
+//! to the clipboard: its [FPGA::to_bytes] encoded as base64,
+//! so that boards can be shared wherever text can be pasted.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use simulator_core::FPGA;
+
+/// Encodes `fpga` as padded base64 text.
+pub(crate) fn encode(fpga: &FPGA) -> String {
+    STANDARD.encode(fpga.to_bytes())
+}
+
+/// Decodes the base64 `text` made by [encode], ignoring
+/// any whitespace around and inside it.
+pub(crate) fn decode(text: &str) -> Result<FPGA> {
+    let text: Vec<u8> = text.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    let bytes = STANDARD
+        .decode(text)
+        .context("The clipboard doesn't contain an FPGA")?;
+    FPGA::try_from(bytes.as_slice()).map_err(anyhow::Error::msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for (width, height) in [(0, 0), (1, 1), (3, 2), (4, 3), (7, 5)] {
+            let mut fpga = FPGA::new(width, height);
+            if let Some(cell) = fpga.get_mut(0, 0) {
+                cell.flags.toggle(simulator_core::cell::CellFlags::NOT_C1);
+            }
+
+            let text = encode(&fpga);
+            assert!(text.len().is_multiple_of(4));
+            assert_eq!(decode(&text).unwrap(), fpga);
+        }
+
+        let text = encode(&FPGA::new(3, 2));
+        let (head, tail) = text.split_at(text.len() / 2);
+        assert_eq!(
+            decode(&format!(" {head}\n{tail} ")).unwrap(),
+            FPGA::new(3, 2)
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let text = encode(&FPGA::new(3, 2));
+
+        assert!(decode("Hello, world!").is_err());
+        assert!(decode(&text[..text.len() - 4]).is_err());
+        assert!(decode(&format!("====={text}")).is_err());
+        assert!(decode(&text.replace('A', "*")).is_err());
+    }
+}
@@ -1,20 +1,60 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use rfd::FileDialog;
-use simulator_core::FPGA;
+use serde::{Deserialize, Serialize};
+use simulator_core::cell::{Cell, CellIO};
+use simulator_core::{CompiledFpga, FPGA};
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
 
+#[cfg(feature = "clipboard")]
+pub(crate) mod clipboard;
+
+/// The payload stored after the file header.
+#[derive(Serialize, Deserialize)]
+enum Payload {
+    /// Every cell stored one after the other, still read but
+    /// no longer written.
+    Plain(FPGA),
+    /// Consecutive identical cells stored only once together
+    /// with their repetition count.
+    RunLength {
+        width: usize,
+        height: usize,
+        runs: Vec<(usize, Cell)>,
+    },
+}
+
+/// The labels the user annotated cells with, by their `(row, col)`.
+pub(crate) type Labels = BTreeMap<(usize, usize), String>;
+
 #[derive(Debug, Default)]
 pub struct File {
     path: Option<PathBuf>,
     pub(crate) fpga: FPGA,
+    /// Kept next to the [FPGA] rather than in its cells, which
+    /// are plain `Copy` data, and stored after the payload.
+    labels: Labels,
+    /// Whether the truth table of every cell is saved too, so that
+    /// the file loads with its [CompiledFpga] already built.
+    pub(crate) embed_tables: bool,
+    /// The tables loaded with the FPGA, dropped by the methods
+    /// which change it since they would no longer match.
+    compiled: Option<CompiledFpga>,
 }
 
 impl File {
+    /// Marks a file written with a format header, files
+    /// without it are loaded as a raw postcard [FPGA].
+    const MAGIC: &'static [u8; 4] = b"GBFS";
+    /// Version 2 added the cell labels after the payload and
+    /// version 3 the optional truth tables after the labels.
+    const FORMAT_VERSION: u8 = 3;
+
     pub(crate) fn save_fpga(&self) -> Result<()> {
         let mut file = fs::File::create(self.path.as_ref().context("No Path specified")?)?;
-        let encoded = postcard::to_allocvec(&self.fpga)?;
+        let encoded = Self::encode(&self.fpga, &self.labels, self.embed_tables)?;
         file.write_all(&encoded)?;
 
         Ok(())
@@ -22,39 +62,268 @@ impl File {
 
     pub(crate) fn load_fpga(&mut self) -> Result<()> {
         let data = fs::read(self.path.as_ref().context("No Path specified")?)?;
-        self.fpga = postcard::from_bytes(&data)?;
+        (self.fpga, self.labels, self.compiled) = Self::decode(&data)?;
+        self.embed_tables = self.compiled.is_some();
 
         Ok(())
     }
 
-    pub fn open_file_dialog(&mut self) -> Result<()> {
-        self.path = FileDialog::new()
+    /// Encodes `fpga` run-length compressed followed by its
+    /// `labels` and, if `embed_tables`, the truth tables of its
+    /// cells, prefixed by the file header.
+    pub(crate) fn encode(fpga: &FPGA, labels: &Labels, embed_tables: bool) -> Result<Vec<u8>> {
+        let mut runs: Vec<(usize, Cell)> = Vec::new();
+        for cell in fpga.cells() {
+            match runs.last_mut() {
+                Some((count, last)) if last == cell => *count += 1,
+                _ => runs.push((1, *cell)),
+            }
+        }
+        let payload = Payload::RunLength {
+            width: fpga.width(),
+            height: fpga.height(),
+            runs,
+        };
+
+        let mut encoded = Self::MAGIC.to_vec();
+        encoded.push(Self::FORMAT_VERSION);
+
+        let tables = embed_tables.then(|| fpga.compile().tables().to_vec());
+
+        let encoded = postcard::to_extend(&payload, encoded)?;
+        let encoded = postcard::to_extend(labels, encoded)?;
+        Ok(postcard::to_extend(&tables, encoded)?)
+    }
+
+    /// Decodes an [FPGA], its labels and its embedded truth tables
+    /// previously encoded by [File::encode]. Data without the file
+    /// header is read as a raw postcard [FPGA] for compatibility
+    /// with older files, which, like the version 1 ones, have no
+    /// labels. Only version 3 files can have tables.
+    ///
+    /// The cells are normalized with [FPGA::normalize], as defense
+    /// in depth since deserializing their flags already restores
+    /// the STILL_XY ones, and the tables are computed again to
+    /// check that they match them.
+    pub(crate) fn decode(data: &[u8]) -> Result<(FPGA, Labels, Option<CompiledFpga>)> {
+        let Some(data) = data.strip_prefix(Self::MAGIC.as_slice()) else {
+            let mut fpga = FPGA::try_from(data).map_err(anyhow::Error::msg)?;
+            fpga.normalize();
+            return Ok((fpga, Labels::new(), None));
+        };
+
+        let (&version, data) = data.split_first().context("Missing format version")?;
+        if !(1..=Self::FORMAT_VERSION).contains(&version) {
+            bail!("Unsupported format version {version}");
+        }
+
+        let (payload, data) = postcard::take_from_bytes(data)?;
+        let mut fpga = Self::decode_payload(payload)?;
+        fpga.normalize();
+        let (labels, tables): (Labels, Option<Vec<[CellIO; 16]>>) = match version {
+            1 => (Labels::new(), None),
+            2 => (postcard::from_bytes(data)?, None),
+            _ => {
+                let (labels, data) = postcard::take_from_bytes(data)?;
+                (labels, postcard::from_bytes(data)?)
+            }
+        };
+
+        if let Some((row, col)) = labels
+            .keys()
+            .find(|(row, col)| fpga.get_cell(*row, *col).is_none())
+        {
+            bail!("Cell ({row}, {col}) has a label but is outside the FPGA");
+        }
+
+        let compiled = match tables {
+            Some(tables) => {
+                let compiled = CompiledFpga::from_tables(fpga.width(), fpga.height(), tables)
+                    .map_err(anyhow::Error::msg)?;
+                if let Err(problems) = compiled.validate(&fpga) {
+                    bail!(
+                        "The embedded truth tables don't match the FPGA: {}",
+                        problems.join(", ")
+                    );
+                }
+                Some(compiled)
+            }
+            None => None,
+        };
+
+        Ok((fpga, labels, compiled))
+    }
+
+    fn decode_payload(payload: Payload) -> Result<FPGA> {
+        match payload {
+            // Deserializing doesn't check that the cells match the
+            // dimensions, so the board is built again from them.
+            Payload::Plain(fpga) => {
+                FPGA::from_cells(fpga.width(), fpga.height(), fpga.cells().copied().collect())
+                    .map_err(anyhow::Error::msg)
+            }
+            Payload::RunLength {
+                width,
+                height,
+                runs,
+            } => {
+                let total = width
+                    .checked_mul(height)
+                    .context("Cell count does not match FPGA dimensions")?;
+                // Not preallocated from the header, which can't be
+                // trusted, but grown as the runs are expanded.
+                let mut cells = Vec::new();
+                for (count, cell) in runs {
+                    if count > total - cells.len() {
+                        bail!("Cell count does not match FPGA dimensions");
+                    }
+                    cells.extend(std::iter::repeat_n(cell, count));
+                }
+
+                FPGA::from_cells(width, height, cells).map_err(anyhow::Error::msg)
+            }
+        }
+    }
+
+    /// Asks the user which file to open and loads it.
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok(false)] without changing anything if no file was chosen.
+    /// - [Ok(true)] once the chosen file has been loaded.
+    /// - [Err()] if it couldn't be loaded, the previous path and
+    ///   FPGA are kept.
+    pub fn open_file_dialog(&mut self) -> Result<bool> {
+        let Some(path) = FileDialog::new()
             .add_filter("FPGA Configuration File", &["fpga", "bit"])
             .add_filter("All Files", &["*"])
             .set_title("Choose a FPGA configuration file")
-            .pick_file();
+            .pick_file()
+        else {
+            return Ok(false);
+        };
 
-        self.load_fpga()?;
+        (self.fpga, self.labels, self.compiled) = Self::decode(&fs::read(&path)?)?;
+        self.embed_tables = self.compiled.is_some();
+        self.path = Some(path);
 
-        Ok(())
+        Ok(true)
     }
 
-    pub fn save_as(&mut self) -> Result<()> {
-        self.path = FileDialog::new()
+    /// Asks the user where to save the FPGA and saves it there.
+    ///
+    /// ## Returns
+    ///
+    /// - [Ok(false)] without saving if no file was chosen.
+    /// - [Ok(true)] once the FPGA has been saved.
+    pub fn save_as(&mut self) -> Result<bool> {
+        let Some(path) = FileDialog::new()
             .add_filter("FPGA Configuration File", &["fpga", "bit"])
             .add_filter("All Files", &["*"])
             .set_title("Choose a FPGA configuration file")
-            .save_file();
+            .save_file()
+        else {
+            return Ok(false);
+        };
 
+        self.path = Some(path);
         self.save_fpga()?;
 
-        Ok(())
+        Ok(true)
     }
 
     pub fn save(&self) -> Result<()> {
         self.save_fpga()
     }
 
+    /// Checks that a board of the given dimensions can be created.
+    pub(crate) fn validate_dimensions(width: usize, height: usize) -> Result<(), &'static str> {
+        if width == 0 {
+            return Err("Width must be greater than 0");
+        }
+        if height == 0 {
+            return Err("Height must be greater than 0");
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the loaded FPGA with a blank one of the given
+    /// dimensions, which isn't saved anywhere yet.
+    pub fn new_fpga(&mut self, width: usize, height: usize) {
+        self.path = None;
+        self.fpga = FPGA::new(width, height);
+        self.labels.clear();
+        self.compiled = None;
+    }
+
+    /// Replaces the loaded FPGA with `fpga`, dropping the labels
+    /// of the previous one but keeping the path.
+    #[cfg(feature = "clipboard")]
+    pub fn replace_fpga(&mut self, fpga: FPGA) {
+        self.fpga = fpga;
+        self.labels.clear();
+        self.compiled = None;
+    }
+
+    /// Replaces the `(row, col)` cell with `cell`.
+    pub fn set_cell(&mut self, row: usize, col: usize, cell: Cell) -> Result<()> {
+        *self
+            .fpga
+            .get_mut(row, col)
+            .with_context(|| format!("Cell ({row}, {col}) is outside the FPGA"))? = cell;
+        self.compiled = None;
+
+        Ok(())
+    }
+
+    /// Edits every cell of `region` with `f`, see [FPGA::apply_to_region].
+    pub fn apply_to_region(
+        &mut self,
+        region: impl IntoIterator<Item = (usize, usize)>,
+        f: impl FnMut(&mut Cell),
+    ) -> Result<()> {
+        self.fpga
+            .apply_to_region(region, f)
+            .map_err(anyhow::Error::msg)?;
+        self.compiled = None;
+
+        Ok(())
+    }
+
+    /// The truth tables embedded in the loaded file, if it had
+    /// them and the FPGA wasn't changed since.
+    pub fn compiled(&self) -> Option<&CompiledFpga> {
+        self.compiled.as_ref()
+    }
+
+    /// The label of the `(row, col)` cell, if it has one.
+    pub fn label(&self, row: usize, col: usize) -> Option<&str> {
+        self.labels.get(&(row, col)).map(String::as_str)
+    }
+
+    /// Iterates over the labeled cells, sorted by position.
+    pub fn labels(&self) -> impl Iterator<Item = ((usize, usize), &str)> {
+        self.labels
+            .iter()
+            .map(|(position, label)| (*position, label.as_str()))
+    }
+
+    /// Sets the label of the `(row, col)` cell, removing it
+    /// if `label` is [None] or empty.
+    pub fn set_label(&mut self, row: usize, col: usize, label: Option<String>) -> Result<()> {
+        if self.fpga.get_cell(row, col).is_none() {
+            bail!("Cell ({row}, {col}) is outside the FPGA");
+        }
+
+        match label.filter(|label| !label.is_empty()) {
+            Some(label) => self.labels.insert((row, col), label),
+            None => self.labels.remove(&(row, col)),
+        };
+
+        Ok(())
+    }
+
     pub fn get_path(&self) -> Option<&PathBuf> {
         self.path.as_ref()
     }
@@ -67,3 +336,154 @@ impl File {
         self.path = path;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes a version 3 file without labels or tables,
+    /// with a hand-built `payload`.
+    fn encode_payload(payload: &Payload) -> Vec<u8> {
+        let mut encoded = File::MAGIC.to_vec();
+        encoded.push(File::FORMAT_VERSION);
+        let encoded = postcard::to_extend(payload, encoded).unwrap();
+        let encoded = postcard::to_extend(&Labels::new(), encoded).unwrap();
+        postcard::to_extend(&None::<Vec<[CellIO; 16]>>, encoded).unwrap()
+    }
+
+    #[test]
+    fn run_length_round_trip() {
+        let fpga = FPGA::new(100, 100);
+
+        let plain = encode_payload(&Payload::Plain(fpga.clone()));
+        let compressed = File::encode(&fpga, &Labels::new(), false).unwrap();
+
+        assert!(compressed.len() * 100 < plain.len());
+
+        let (decoded, _, _) = File::decode(&compressed).unwrap();
+        assert_eq!(decoded.width(), 100);
+        assert_eq!(decoded.height(), 100);
+        assert!(decoded.cells().eq(fpga.cells()));
+
+        let (decoded, _, _) = File::decode(&plain).unwrap();
+        assert!(decoded.cells().eq(fpga.cells()));
+    }
+
+    #[test]
+    fn corrupted_payloads_rejected() {
+        let decode = |payload: Payload| File::decode(&encode_payload(&payload));
+
+        let mismatched = postcard::to_allocvec(&(2usize, 2usize, Vec::<Cell>::new())).unwrap();
+        let mismatched: FPGA = postcard::from_bytes(&mismatched).unwrap();
+        assert!(decode(Payload::Plain(mismatched)).is_err());
+
+        assert!(
+            decode(Payload::RunLength {
+                width: usize::MAX,
+                height: 2,
+                runs: Vec::new(),
+            })
+            .is_err()
+        );
+        assert!(
+            decode(Payload::RunLength {
+                width: 2,
+                height: 2,
+                runs: vec![(3, Cell::default()), (usize::MAX, Cell::default())],
+            })
+            .is_err()
+        );
+        assert!(
+            decode(Payload::RunLength {
+                width: 2,
+                height: 2,
+                runs: vec![(3, Cell::default())],
+            })
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn legacy_files_still_load() {
+        let fpga = FPGA::new(4, 3);
+        let legacy = fpga.to_bytes();
+
+        let (decoded, labels, _) = File::decode(&legacy).unwrap();
+        assert_eq!(decoded.width(), 4);
+        assert_eq!(decoded.height(), 3);
+        assert!(labels.is_empty());
+
+        // Version 2 files have nothing after the labels, and
+        // version 1 ones nothing after the payload.
+        let mut version_2 = File::encode(&fpga, &Labels::new(), false).unwrap();
+        version_2[File::MAGIC.len()] = 2;
+        version_2.pop();
+        let (decoded, labels, _) = File::decode(&version_2).unwrap();
+        assert_eq!(decoded, fpga);
+        assert!(labels.is_empty());
+
+        let mut version_1 = version_2;
+        version_1[File::MAGIC.len()] = 1;
+        version_1.pop();
+        let (decoded, labels, _) = File::decode(&version_1).unwrap();
+        assert_eq!(decoded, fpga);
+        assert!(labels.is_empty());
+    }
+
+    #[test]
+    fn labels() {
+        let mut file = File::default();
+        file.new_fpga(3, 2);
+
+        file.set_label(1, 2, Some("Carry".to_string())).unwrap();
+        file.set_label(0, 0, Some("Input".to_string())).unwrap();
+        file.set_label(0, 0, Some(String::new())).unwrap();
+        assert!(file.set_label(2, 0, Some("Outside".to_string())).is_err());
+        assert_eq!(file.label(1, 2), Some("Carry"));
+        assert_eq!(file.label(0, 0), None);
+
+        let encoded = File::encode(&file.fpga, &file.labels, false).unwrap();
+        let (fpga, labels, _) = File::decode(&encoded).unwrap();
+        assert_eq!(fpga, file.fpga);
+        assert_eq!(labels, file.labels);
+
+        let encoded = File::encode(&FPGA::new(2, 2), &file.labels, false).unwrap();
+        assert!(File::decode(&encoded).is_err());
+
+        #[cfg(feature = "clipboard")]
+        {
+            file.replace_fpga(FPGA::new(3, 2));
+            assert_eq!(file.label(1, 2), None);
+        }
+    }
+
+    #[test]
+    fn embedded_tables() {
+        let mut fpga = FPGA::new(3, 2);
+        fpga.get_mut(1, 2).unwrap().flags |= simulator_core::cell::CellFlags::JC1_R1;
+
+        let encoded = File::encode(&fpga, &Labels::new(), true).unwrap();
+        let (decoded, _, compiled) = File::decode(&encoded).unwrap();
+        assert_eq!(decoded, fpga);
+        assert_eq!(compiled, Some(fpga.compile()));
+
+        let plain = File::encode(&fpga, &Labels::new(), false).unwrap();
+        assert!(plain.len() < encoded.len());
+        assert_eq!(File::decode(&plain).unwrap().2, None);
+
+        // The last byte is the output of the last cell when all
+        // its inputs are set, which no longer matches once flipped.
+        let mut tampered = encoded;
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(File::decode(&tampered).is_err());
+
+        let mut file = File {
+            fpga,
+            compiled,
+            ..File::default()
+        };
+        file.set_cell(0, 0, Cell::default()).unwrap();
+        assert_eq!(file.compiled(), None);
+        assert!(file.set_cell(2, 0, Cell::default()).is_err());
+    }
+}
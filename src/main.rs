@@ -1,5 +1,41 @@
+// The acronyms are the established names of these types.
+#![allow(clippy::upper_case_acronyms)]
+
 mod cli;
+mod config;
+mod export;
 mod gui;
 mod io;
+mod theme;
+
+use crate::cli::CLI;
+use crate::config::Config;
+use crate::gui::GUI;
+use crate::io::File;
+use clap::Parser;
+use std::process::ExitCode;
+use std::sync::{Arc, RwLock};
+
+fn main() -> ExitCode {
+    let cli = CLI::parse();
+
+    let result = match cli.command {
+        Some(command) => CLI::run(command),
+        None => {
+            // A broken configuration shouldn't keep the GUI from opening.
+            let config = Config::load().unwrap_or_else(|err| {
+                eprintln!("Warning: {err:#}, using the default configuration");
+                Config::default()
+            });
+            GUI::run(Arc::new(RwLock::new(File::default())), config).map_err(anyhow::Error::from)
+        }
+    };
 
-fn main() {}
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use iced::{Color, Theme};
+use simulator_core::cell::CellFlags;
+use std::fmt;
+
+/// The colors the cells are drawn with, by the viewer
+/// as well as in the exported images.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ViewerTheme {
+    name: &'static str,
+    pub(crate) not: Color,
+    pub(crate) normal: Color,
+    pub(crate) junction: Color,
+    pub(crate) out: Color,
+    /// The lines that carried a signal, in the signal view.
+    pub(crate) signal: Color,
+}
+
+impl Default for ViewerTheme {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+impl fmt::Display for ViewerTheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+impl ViewerTheme {
+    /// The opacity of the pixels of disabled cells.
+    const DISABLED_ALPHA: f32 = 0.3;
+
+    pub(crate) const DARK: ViewerTheme = ViewerTheme {
+        name: "Dark",
+        not: Color::from_rgb(0.45, 0.0, 0.0),
+        normal: Color::from_rgb(0.29, 0.29, 0.32),
+        junction: Color::from_rgb(0.05, 0.9, 0.8),
+        out: Color::from_rgb(0.82, 0.05, 0.88),
+        signal: Color::from_rgb(1.0, 0.85, 0.1),
+    };
+    pub(crate) const LIGHT: ViewerTheme = ViewerTheme {
+        name: "Light",
+        not: Color::from_rgb(0.85, 0.2, 0.2),
+        normal: Color::from_rgb(0.75, 0.75, 0.78),
+        junction: Color::from_rgb(0.0, 0.55, 0.5),
+        out: Color::from_rgb(0.6, 0.0, 0.65),
+        signal: Color::from_rgb(0.95, 0.55, 0.0),
+    };
+    /// Built on the Okabe-Ito palette, which stays
+    /// distinguishable with the common color blindnesses.
+    pub(crate) const HIGH_CONTRAST: ViewerTheme = ViewerTheme {
+        name: "High contrast",
+        not: Color::from_rgb(0.9, 0.6, 0.0),
+        normal: Color::from_rgb(0.85, 0.85, 0.85),
+        junction: Color::from_rgb(0.0, 0.45, 0.7),
+        out: Color::from_rgb(0.8, 0.47, 0.65),
+        signal: Color::from_rgb(0.94, 0.89, 0.26),
+    };
+    pub(crate) const ALL: [ViewerTheme; 3] = [Self::DARK, Self::LIGHT, Self::HIGH_CONTRAST];
+
+    /// The theme of the rest of the window matching the cell colors.
+    pub(crate) fn app_theme(&self) -> Theme {
+        if *self == Self::LIGHT {
+            Theme::Light
+        } else {
+            Theme::Dark
+        }
+    }
+
+    /// Dims `color` if the cell is disabled.
+    #[inline]
+    pub(crate) fn cell_color(color: Color, cell_flags: &CellFlags) -> Color {
+        if cell_flags.contains(CellFlags::DISABLED) {
+            Color {
+                a: color.a * Self::DISABLED_ALPHA,
+                ..color
+            }
+        } else {
+            color
+        }
+    }
+}